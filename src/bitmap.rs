@@ -0,0 +1,328 @@
+use std::borrow::Cow;
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use image::{RgbaImage, imageops::FilterType};
+
+use crate::{blend::BlendMode, color::Color, error::RenderError, geom::Rect};
+
+/// Where the pixels for [`crate::Canvas::draw_image`] come from.
+#[derive(Debug, Clone, Copy)]
+pub enum ImageSource<'a> {
+    /// Encoded image bytes. PNG is always supported; JPEG, WebP and GIF need the
+    /// `jpeg`, `webp` and `gif` cargo features. GIFs render their first frame.
+    Encoded(&'a [u8]),
+    /// A base64-encoded image, with or without a `data:image/…;base64,` prefix.
+    Base64(&'a str),
+    /// Already-decoded RGBA pixels (straight alpha).
+    Rgba(&'a RgbaImage),
+}
+
+impl<'a> From<&'a [u8]> for ImageSource<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Self::Encoded(bytes)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for ImageSource<'a> {
+    fn from(bytes: &'a Vec<u8>) -> Self {
+        Self::Encoded(bytes)
+    }
+}
+
+impl<'a> From<&'a RgbaImage> for ImageSource<'a> {
+    fn from(img: &'a RgbaImage) -> Self {
+        Self::Rgba(img)
+    }
+}
+
+/// How an image is sized and positioned within its destination rectangle.
+///
+/// Mirrors CSS `object-fit`. Anything falling outside the destination is clipped.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ImageFit {
+    /// Scale uniformly to fit entirely inside the rect, centered (letterboxed).
+    #[default]
+    Contain,
+    /// Scale uniformly to cover the whole rect, centered, cropping the overflow.
+    Cover,
+    /// Stretch to exactly fill the rect, ignoring the aspect ratio.
+    Fill,
+    /// Draw at natural size, centered.
+    None,
+    /// Like [`ImageFit::Cover`], but keep the focal point `(x, y)` — given as
+    /// fractions of the image size, `(0.5, 0.5)` being the center — in view.
+    Focal { x: f32, y: f32 },
+}
+
+/// Resampling filter used when the image is drawn at a different size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageQuality {
+    /// Nearest neighbour: blocky, but keeps pixel art crisp.
+    Nearest,
+    /// Bilinear (triangle) filtering.
+    Bilinear,
+    /// Catmull-Rom bicubic filtering: sharp and fast.
+    #[default]
+    Bicubic,
+    /// Lanczos3 filtering: sharpest for large downscales, slowest.
+    Lanczos,
+}
+
+/// Options controlling how an image is drawn onto the canvas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageOptions {
+    pub fit: ImageFit,
+    /// Opacity multiplier in `[0, 1]`.
+    pub opacity: f32,
+    pub quality: ImageQuality,
+    /// Blend mode for this draw. `None` uses the canvas's current blend mode.
+    pub blend_mode: Option<BlendMode>,
+}
+
+impl ImageOptions {
+    /// Contain-fit, fully opaque, bicubic resampling.
+    pub fn new() -> Self {
+        Self {
+            fit: ImageFit::Contain,
+            opacity: 1.0,
+            quality: ImageQuality::Bicubic,
+            blend_mode: None,
+        }
+    }
+
+    pub fn fit(mut self, fit: ImageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn quality(mut self, quality: ImageQuality) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn blend_mode(mut self, mode: BlendMode) -> Self {
+        self.blend_mode = Some(mode);
+        self
+    }
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode an [`ImageSource`] into RGBA pixels, borrowing when already decoded.
+pub(crate) fn decode(src: ImageSource<'_>) -> Result<Cow<'_, RgbaImage>, RenderError> {
+    match src {
+        ImageSource::Rgba(img) => Ok(Cow::Borrowed(img)),
+        ImageSource::Encoded(bytes) => decode_bytes(bytes).map(Cow::Owned),
+        ImageSource::Base64(s) => {
+            // Accept full data URIs as produced by `RenderedImage::to_data_url`.
+            let payload = match s.split_once(";base64,") {
+                Some((_, data)) => data,
+                None => s,
+            };
+            let bytes = BASE64_STANDARD.decode(payload.trim())?;
+            decode_bytes(&bytes).map(Cow::Owned)
+        }
+    }
+}
+
+fn decode_bytes(bytes: &[u8]) -> Result<RgbaImage, RenderError> {
+    image::load_from_memory(bytes)
+        .map(|img| img.into_rgba8())
+        .map_err(RenderError::ImageDecode)
+}
+
+/// Compute where an image of natural size `(iw, ih)` lands inside `dest`.
+pub(crate) fn place(iw: f32, ih: f32, dest: &Rect, fit: ImageFit) -> Rect {
+    let (sx, sy) = (dest.width / iw, dest.height / ih);
+    let (w, h, fx, fy) = match fit {
+        ImageFit::Fill => return *dest,
+        ImageFit::None => (iw, ih, 0.5, 0.5),
+        ImageFit::Contain => {
+            let s = sx.min(sy);
+            (iw * s, ih * s, 0.5, 0.5)
+        }
+        ImageFit::Cover => {
+            let s = sx.max(sy);
+            (iw * s, ih * s, 0.5, 0.5)
+        }
+        ImageFit::Focal { x, y } => {
+            let s = sx.max(sy);
+            (iw * s, ih * s, x.clamp(0.0, 1.0), y.clamp(0.0, 1.0))
+        }
+    };
+    // Slide the overflow (or slack) so the focal fraction lines up on both.
+    Rect::new(
+        dest.x + (dest.width - w) * fx,
+        dest.y + (dest.height - h) * fy,
+        w,
+        h,
+    )
+}
+
+/// Crop `img`, drawn over `placed`, to the whole source pixels that reach into
+/// `clip`. Returns the pixels and the rect they now cover, so an image that
+/// overflows its destination (e.g. [`ImageFit::Cover`] with an extreme aspect
+/// ratio) is never resampled at its full covering size.
+pub(crate) fn crop_to_clip<'a>(
+    img: Cow<'a, RgbaImage>,
+    placed: Rect,
+    clip: &Rect,
+) -> (Cow<'a, RgbaImage>, Rect) {
+    let (iw, ih) = img.dimensions();
+    let (kx, ky) = (placed.width / iw as f32, placed.height / ih as f32);
+    let edge = |v: f32, max: u32| v.clamp(0.0, max as f32) as u32;
+    let x0 = edge(((clip.x - placed.x) / kx).floor(), iw);
+    let y0 = edge(((clip.y - placed.y) / ky).floor(), ih);
+    let x1 = edge(((clip.right() - placed.x) / kx).ceil(), iw);
+    let y1 = edge(((clip.bottom() - placed.y) / ky).ceil(), ih);
+    if (x0, y0, x1, y1) == (0, 0, iw, ih) || x1 <= x0 || y1 <= y0 {
+        return (img, placed);
+    }
+    let (w, h) = (x1 - x0, y1 - y0);
+    let cropped = image::imageops::crop_imm(&*img, x0, y0, w, h).to_image();
+    let rect = Rect::new(
+        placed.x + x0 as f32 * kx,
+        placed.y + y0 as f32 * ky,
+        w as f32 * kx,
+        h as f32 * ky,
+    );
+    (Cow::Owned(cropped), rect)
+}
+
+/// Resample `img` to `width × height` with premultiplied alpha, so transparent
+/// pixels don't bleed dark fringes into their neighbours.
+pub(crate) fn resize(img: &RgbaImage, width: u32, height: u32, quality: ImageQuality) -> RgbaImage {
+    let filter = match quality {
+        ImageQuality::Nearest => FilterType::Nearest,
+        ImageQuality::Bilinear => FilterType::Triangle,
+        ImageQuality::Bicubic => FilterType::CatmullRom,
+        ImageQuality::Lanczos => FilterType::Lanczos3,
+    };
+
+    let mut premul = img.clone();
+    for p in premul.pixels_mut() {
+        let a = p[3] as u16;
+        for c in &mut p.0[..3] {
+            *c = ((*c as u16 * a + 127) / 255) as u8;
+        }
+    }
+
+    let mut out = image::imageops::resize(&premul, width, height, filter);
+    for p in out.pixels_mut() {
+        let a = p[3] as u16;
+        for c in &mut p.0[..3] {
+            let straight = (*c as u16 * 255 + a / 2).checked_div(a).unwrap_or(0);
+            *c = straight.min(255) as u8;
+        }
+    }
+    out
+}
+
+/// Bilinearly sample `img` at `(x, y)` in pixel-center coordinates, clamping to
+/// the edges. Interpolates premultiplied, like [`resize`].
+pub(crate) fn sample_bilinear(img: &RgbaImage, x: f32, y: f32) -> Color {
+    let max_x = img.width() as f32 - 1.0;
+    let max_y = img.height() as f32 - 1.0;
+    let (x, y) = (x.clamp(0.0, max_x), y.clamp(0.0, max_y));
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as u32, y0 as u32);
+    let (x1, y1) = ((x0 + 1).min(max_x as u32), (y0 + 1).min(max_y as u32));
+
+    let mut acc = [0.0_f32; 4];
+    for (px, py, w) in [
+        (x0, y0, (1.0 - fx) * (1.0 - fy)),
+        (x1, y0, fx * (1.0 - fy)),
+        (x0, y1, (1.0 - fx) * fy),
+        (x1, y1, fx * fy),
+    ] {
+        let p = img.get_pixel(px, py);
+        let a = p[3] as f32 * w;
+        for (c, v) in acc[..3].iter_mut().zip(&p.0[..3]) {
+            *c += *v as f32 * a;
+        }
+        acc[3] += a;
+    }
+    if acc[3] <= 0.0 {
+        return Color::TRANSPARENT;
+    }
+    let q = |v: f32| v.round().clamp(0.0, 255.0) as u8;
+    Color::rgba(
+        q(acc[0] / acc[3]),
+        q(acc[1] / acc[3]),
+        q(acc[2] / acc[3]),
+        q(acc[3]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEST: Rect = Rect::new(0.0, 0.0, 100.0, 50.0);
+
+    #[test]
+    fn contain_letterboxes() {
+        let r = place(200.0, 200.0, &DEST, ImageFit::Contain);
+        assert_eq!(r, Rect::new(25.0, 0.0, 50.0, 50.0));
+    }
+
+    #[test]
+    fn cover_crops() {
+        let r = place(200.0, 200.0, &DEST, ImageFit::Cover);
+        assert_eq!(r, Rect::new(0.0, -25.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn cover_crop_stays_near_destination_size() {
+        let tall = RgbaImage::new(1, 4000);
+        let dest = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let placed = place(1.0, 4000.0, &dest, ImageFit::Cover);
+        let (img, rect) = crop_to_clip(Cow::Owned(tall), placed, &dest);
+        // One source pixel wide, and only the rows overlapping the destination.
+        assert_eq!(img.dimensions(), (1, 2));
+        assert!(rect.height <= 20.0 && rect.y <= 0.0 && rect.bottom() >= 10.0);
+    }
+
+    #[test]
+    fn focal_point_keeps_top_in_view() {
+        let r = place(200.0, 200.0, &DEST, ImageFit::Focal { x: 0.5, y: 0.0 });
+        assert_eq!(r, Rect::new(0.0, 0.0, 100.0, 100.0));
+    }
+
+    #[test]
+    fn none_keeps_natural_size() {
+        let r = place(20.0, 10.0, &DEST, ImageFit::None);
+        assert_eq!(r, Rect::new(40.0, 20.0, 20.0, 10.0));
+    }
+
+    #[test]
+    fn bilinear_blends_neighbours() {
+        let mut img = RgbaImage::new(2, 1);
+        img.put_pixel(0, 0, image::Rgba([0, 0, 0, 255]));
+        img.put_pixel(1, 0, image::Rgba([200, 0, 0, 255]));
+        assert_eq!(sample_bilinear(&img, 0.5, 0.0), Color::rgb(100, 0, 0));
+        assert_eq!(sample_bilinear(&img, -3.0, 0.0), Color::rgb(0, 0, 0));
+    }
+
+    #[test]
+    fn base64_data_uri_round_trip() {
+        let img = RgbaImage::from_pixel(2, 2, image::Rgba([10, 20, 30, 255]));
+        let mut png = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let uri = format!("data:image/png;base64,{}", BASE64_STANDARD.encode(&png));
+
+        let decoded = decode(ImageSource::Base64(&uri)).unwrap();
+        assert_eq!(*decoded, img);
+    }
+}
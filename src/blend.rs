@@ -0,0 +1,342 @@
+use image::Rgba;
+
+use crate::color::Color;
+
+/// How source pixels are combined with the pixels already on the canvas.
+///
+/// The separable modes (`Multiply` … `Exclusion`) follow the W3C Compositing and
+/// Blending spec and are composited source-over. The Porter-Duff operators only
+/// affect pixels the draw actually covers: anything outside the shape being
+/// drawn is left untouched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Normal alpha compositing: source over destination.
+    #[default]
+    SourceOver,
+
+    // ── Separable blend modes ───────────────────────────────────────────────
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    /// Additive: channels are summed and clamped ("plus-lighter").
+    Add,
+
+    // ── Porter-Duff operators ───────────────────────────────────────────────
+    /// Clear the covered pixels.
+    Clear,
+    /// Replace the destination with the source.
+    Source,
+    /// Draw behind the existing content.
+    DestinationOver,
+    /// Keep the source only where the destination is opaque.
+    SourceIn,
+    /// Keep the destination only where the source is opaque.
+    DestinationIn,
+    /// Keep the source only where the destination is transparent.
+    SourceOut,
+    /// Erase the destination where the source is opaque (knock-out).
+    DestinationOut,
+    /// Draw the source on top of the destination, only where it is opaque.
+    SourceAtop,
+    /// Keep the destination on top of the source, only where the source is opaque.
+    DestinationAtop,
+    /// Keep source and destination only where they don't overlap.
+    Xor,
+}
+
+impl BlendMode {
+    /// Porter-Duff source/destination factors `(Fa, Fb)`, or `None` for separable modes.
+    fn porter_duff(self, sa: f32, da: f32) -> Option<(f32, f32)> {
+        Some(match self {
+            BlendMode::Clear => (0.0, 0.0),
+            BlendMode::Source => (1.0, 0.0),
+            BlendMode::DestinationOver => (1.0 - da, 1.0),
+            BlendMode::SourceIn => (da, 0.0),
+            BlendMode::DestinationIn => (0.0, sa),
+            BlendMode::SourceOut => (1.0 - da, 0.0),
+            BlendMode::DestinationOut => (0.0, 1.0 - sa),
+            BlendMode::SourceAtop => (da, 1.0 - sa),
+            BlendMode::DestinationAtop => (1.0 - da, sa),
+            BlendMode::Xor => (1.0 - da, 1.0 - sa),
+            _ => return None,
+        })
+    }
+
+    /// Separable blend function `B(cb, cs)` on straight channel values in `[0, 1]`.
+    fn blend_channel(self, cb: f32, cs: f32) -> f32 {
+        match self {
+            BlendMode::Multiply => cb * cs,
+            BlendMode::Screen => cb + cs - cb * cs,
+            BlendMode::Overlay => BlendMode::HardLight.blend_channel(cs, cb),
+            BlendMode::Darken => cb.min(cs),
+            BlendMode::Lighten => cb.max(cs),
+            BlendMode::ColorDodge => {
+                if cb == 0.0 {
+                    0.0
+                } else if cs >= 1.0 {
+                    1.0
+                } else {
+                    (cb / (1.0 - cs)).min(1.0)
+                }
+            }
+            BlendMode::ColorBurn => {
+                if cb >= 1.0 {
+                    1.0
+                } else if cs <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - cb) / cs).min(1.0)
+                }
+            }
+            BlendMode::HardLight => {
+                if cs <= 0.5 {
+                    cb * 2.0 * cs
+                } else {
+                    BlendMode::Screen.blend_channel(cb, 2.0 * cs - 1.0)
+                }
+            }
+            BlendMode::SoftLight => {
+                if cs <= 0.5 {
+                    cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+                } else {
+                    let d = if cb <= 0.25 {
+                        ((16.0 * cb - 12.0) * cb + 4.0) * cb
+                    } else {
+                        cb.sqrt()
+                    };
+                    cb + (2.0 * cs - 1.0) * (d - cb)
+                }
+            }
+            BlendMode::Difference => (cb - cs).abs(),
+            BlendMode::Exclusion => cb + cs - 2.0 * cb * cs,
+            _ => cs,
+        }
+    }
+}
+
+/// Composite `src` onto `dst` with `mode`, applied with partial `coverage` in `[0, 1]`.
+///
+/// The operator is evaluated at full coverage and the result interpolated with
+/// the original destination, so anti-aliased edges stay correct for every mode.
+pub(crate) fn blend(dst: &mut Rgba<u8>, src: Color, coverage: f32, mode: BlendMode) {
+    if mode == BlendMode::SourceOver {
+        composite_over(dst, src, coverage * (src.a as f32 / 255.0));
+        return;
+    }
+
+    let unit = |v: u8| v as f32 / 255.0;
+    let sa = unit(src.a);
+    let da = unit(dst[3]);
+    let s = [unit(src.r), unit(src.g), unit(src.b)];
+    let d = [unit(dst[0]), unit(dst[1]), unit(dst[2])];
+
+    // Premultiplied result of the operator at full coverage.
+    let (rgb, out_a) = if let Some((fa, fb)) = mode.porter_duff(sa, da) {
+        let c = |i: usize| s[i] * sa * fa + d[i] * da * fb;
+        ([c(0), c(1), c(2)], sa * fa + da * fb)
+    } else if mode == BlendMode::Add {
+        let c = |i: usize| (s[i] * sa + d[i] * da).min(1.0);
+        ([c(0), c(1), c(2)], (sa + da).min(1.0))
+    } else {
+        let c = |i: usize| {
+            s[i] * sa * (1.0 - da)
+                + d[i] * da * (1.0 - sa)
+                + sa * da * mode.blend_channel(d[i], s[i])
+        };
+        ([c(0), c(1), c(2)], sa + da * (1.0 - sa))
+    };
+
+    // Interpolate with the untouched destination by coverage, still premultiplied.
+    let cov = coverage.clamp(0.0, 1.0);
+    let a = da + (out_a - da) * cov;
+    if a <= 0.0 {
+        *dst = Rgba([0, 0, 0, 0]);
+        return;
+    }
+    let q = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+    for i in 0..3 {
+        let premul = d[i] * da + (rgb[i] - d[i] * da) * cov;
+        dst[i] = q(premul / a);
+    }
+    dst[3] = q(a);
+}
+
+/// Composite premultiplied `src` onto premultiplied `dst` with `mode`, applied
+/// with partial `coverage` in `[0, 1]`. The float counterpart of [`blend`].
+pub(crate) fn blend_premul(dst: &mut [f32; 4], src: [f32; 4], coverage: f32, mode: BlendMode) {
+    let cov = coverage.clamp(0.0, 1.0);
+    let (sa, da) = (src[3], dst[3]);
+
+    if mode == BlendMode::SourceOver {
+        let k = 1.0 - sa * cov;
+        for i in 0..4 {
+            dst[i] = src[i] * cov + dst[i] * k;
+        }
+        return;
+    }
+
+    let out: [f32; 4] = if let Some((fa, fb)) = mode.porter_duff(sa, da) {
+        std::array::from_fn(|i| src[i] * fa + dst[i] * fb)
+    } else if mode == BlendMode::Add {
+        std::array::from_fn(|i| (src[i] + dst[i]).min(1.0))
+    } else {
+        let straight = |c: f32, a: f32| if a > 0.0 { c / a } else { 0.0 };
+        let mut out: [f32; 4] = std::array::from_fn(|i| {
+            src[i] * (1.0 - da)
+                + dst[i] * (1.0 - sa)
+                + sa * da * mode.blend_channel(straight(dst[i], da), straight(src[i], sa))
+        });
+        out[3] = sa + da * (1.0 - sa);
+        out
+    };
+
+    for i in 0..4 {
+        dst[i] += (out[i] - dst[i]) * cov;
+    }
+}
+
+/// Porter-Duff "source over destination" compositing.
+///
+/// `src_alpha` is the pre-multiplied effective alpha of the source (already in `[0,1]`).
+#[inline]
+pub(crate) fn composite_over(dst: &mut Rgba<u8>, src_color: Color, src_alpha: f32) {
+    let dst_a = dst[3] as f32 / 255.0;
+    let out_a = src_alpha + dst_a * (1.0 - src_alpha);
+
+    if out_a <= 0.0 {
+        return;
+    }
+
+    let blend = |src_c: u8, dst_c: u8| -> u8 {
+        let s = src_c as f32 / 255.0;
+        let d = dst_c as f32 / 255.0;
+        ((s * src_alpha + d * dst_a * (1.0 - src_alpha)) / out_a * 255.0).round() as u8
+    };
+
+    dst[0] = blend(src_color.r, dst[0]);
+    dst[1] = blend(src_color.g, dst[1]);
+    dst[2] = blend(src_color.b, dst[2]);
+    dst[3] = (out_a * 255.0).round() as u8;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn px(r: u8, g: u8, b: u8, a: u8) -> Rgba<u8> {
+        Rgba([r, g, b, a])
+    }
+
+    #[test]
+    fn multiply_darkens() {
+        let mut dst = px(200, 100, 50, 255);
+        blend(
+            &mut dst,
+            Color::rgb(128, 128, 128),
+            1.0,
+            BlendMode::Multiply,
+        );
+        assert_eq!(dst, px(100, 50, 25, 255));
+    }
+
+    #[test]
+    fn screen_lightens() {
+        let mut dst = px(0, 128, 255, 255);
+        blend(&mut dst, Color::rgb(128, 128, 128), 1.0, BlendMode::Screen);
+        assert_eq!(dst, px(128, 192, 255, 255));
+    }
+
+    #[test]
+    fn add_clamps() {
+        let mut dst = px(200, 10, 0, 255);
+        blend(&mut dst, Color::rgb(100, 10, 0), 1.0, BlendMode::Add);
+        assert_eq!(dst, px(255, 20, 0, 255));
+    }
+
+    #[test]
+    fn difference_of_equal_is_black() {
+        let mut dst = px(90, 90, 90, 255);
+        blend(&mut dst, Color::rgb(90, 90, 90), 1.0, BlendMode::Difference);
+        assert_eq!(dst, px(0, 0, 0, 255));
+    }
+
+    #[test]
+    fn destination_out_erases() {
+        let mut dst = px(255, 0, 0, 255);
+        blend(&mut dst, Color::WHITE, 1.0, BlendMode::DestinationOut);
+        assert_eq!(dst[3], 0);
+
+        let mut dst = px(255, 0, 0, 255);
+        blend(&mut dst, Color::WHITE, 0.5, BlendMode::DestinationOut);
+        assert_eq!(dst, px(255, 0, 0, 128));
+    }
+
+    #[test]
+    fn source_in_needs_destination() {
+        let mut empty = px(0, 0, 0, 0);
+        blend(&mut empty, Color::WHITE, 1.0, BlendMode::SourceIn);
+        assert_eq!(empty[3], 0);
+
+        let mut opaque = px(255, 0, 0, 255);
+        blend(&mut opaque, Color::rgb(0, 255, 0), 1.0, BlendMode::SourceIn);
+        assert_eq!(opaque, px(0, 255, 0, 255));
+    }
+
+    #[test]
+    fn xor_of_opaque_clears() {
+        let mut dst = px(255, 0, 0, 255);
+        blend(&mut dst, Color::WHITE, 1.0, BlendMode::Xor);
+        assert_eq!(dst[3], 0);
+    }
+
+    #[test]
+    fn premul_matches_straight() {
+        let modes = [
+            BlendMode::SourceOver,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::DestinationOut,
+            BlendMode::Xor,
+        ];
+        for mode in modes {
+            let src = Color::rgba(200, 40, 90, 180);
+            let mut straight = px(30, 160, 220, 200);
+            blend(&mut straight, src, 0.7, mode);
+
+            let (sa, da) = (180.0 / 255.0, 200.0 / 255.0);
+            let mut premul = [
+                30.0 / 255.0 * da,
+                160.0 / 255.0 * da,
+                220.0 / 255.0 * da,
+                da,
+            ];
+            let s = [200.0 / 255.0 * sa, 40.0 / 255.0 * sa, 90.0 / 255.0 * sa, sa];
+            blend_premul(&mut premul, s, 0.7, mode);
+
+            let a = premul[3];
+            assert_eq!(straight[3], (a * 255.0).round() as u8, "{mode:?}");
+            for i in 0..3 {
+                let c = (premul[i] / a * 255.0).round() as u8;
+                assert!(
+                    straight[i].abs_diff(c) <= 1,
+                    "{mode:?}: {straight:?} vs {premul:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn separable_over_transparent_is_source() {
+        let mut dst = px(0, 0, 0, 0);
+        blend(&mut dst, Color::rgb(10, 20, 30), 1.0, BlendMode::Multiply);
+        assert_eq!(dst, px(10, 20, 30, 255));
+    }
+}
@@ -1,4 +1,6 @@
-use crate::color::Color;
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::{geom::Corner, paint::Paint, shape::LineCap};
 
 /// How to draw the rounded-rectangle frame around the canvas.
 #[derive(Debug, Clone, PartialEq)]
@@ -12,30 +14,119 @@ pub enum BorderStyle {
         thickness: f32,
         /// Corner radius in pixels.
         radius: f32,
-        /// Stroke color.
-        color: Color,
+        /// Stroke color or gradient.
+        color: Paint,
     },
 
     /// Vignette that fades from `color` at the canvas edge inward over `width` pixels.
     ///
-    /// The `color.a` value controls the peak alpha at the very edge. Alpha reaches
+    /// The paint's alpha controls the peak alpha at the very edge. Alpha reaches
     /// zero at `width` pixels inward using a quadratic ease-out falloff.
     Vignette {
         /// How far inward (in pixels) the fade extends before reaching full transparency.
         width: f32,
         /// Corner radius in pixels.
         radius: f32,
-        /// Edge color (alpha = peak alpha at the very edge).
-        color: Color,
+        /// Edge color or gradient (alpha = peak alpha at the very edge).
+        color: Paint,
+    },
+
+    /// Dashed rounded-rect stroke. Dashes follow the corners, and their spacing
+    /// is stretched slightly so a whole number fits around the frame.
+    Dashed {
+        /// Stroke thickness in pixels.
+        thickness: f32,
+        /// Corner radius in pixels.
+        radius: f32,
+        /// Length of each dash along the frame, in pixels.
+        dash: f32,
+        /// Space between dashes, in pixels.
+        gap: f32,
+        /// Stroke color or gradient.
+        color: Paint,
+    },
+
+    /// Round dots of diameter `size` spaced evenly around the frame.
+    Dotted {
+        /// Dot diameter in pixels; the band is this thick.
+        size: f32,
+        /// Corner radius in pixels.
+        radius: f32,
+        /// Space between dots, in pixels.
+        gap: f32,
+        /// Dot color or gradient.
+        color: Paint,
+    },
+
+    /// Two parallel strokes of `thickness` separated by `gap`.
+    Double {
+        /// Thickness of each stroke in pixels.
+        thickness: f32,
+        /// Space between the strokes in pixels.
+        gap: f32,
+        /// Corner radius of the outer stroke in pixels.
+        radius: f32,
+        /// Stroke color or gradient.
+        color: Paint,
+    },
+
+    /// A solid stroke along only part of the frame, for timers and cooldowns
+    /// that fill or drain around the key.
+    ///
+    /// The stroke starts at the middle of the `start` corner and runs
+    /// `fraction` of the way round in `direction`. Draw a dim
+    /// [`BorderStyle::Solid`] first for a visible track.
+    Progress {
+        /// Stroke thickness in pixels.
+        thickness: f32,
+        /// Corner radius in pixels.
+        radius: f32,
+        /// Share of the perimeter stroked, clamped to `[0, 1]`.
+        fraction: f32,
+        /// Corner the stroke starts from.
+        start: Corner,
+        /// Which way the stroke runs from `start`.
+        direction: PerimeterDirection,
+        /// Shape of the two ends. Ignored when `fraction` is 1.
+        cap: LineCap,
+        /// Stroke color or gradient.
+        color: Paint,
+    },
+
+    /// Any other style drawn `inset` pixels in from the canvas edge.
+    Inset {
+        /// Pixels in from the canvas edge.
+        inset: f32,
+        /// The style drawn at that inset.
+        style: Box<BorderStyle>,
     },
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for BorderStyle {
     fn default() -> Self {
         Self::None
     }
 }
 
+impl BorderStyle {
+    /// Move the border `inset` pixels in from the canvas edge.
+    pub fn inset(self, inset: f32) -> Self {
+        Self::Inset {
+            inset,
+            style: Box::new(self),
+        }
+    }
+}
+
+/// Which way a [`BorderStyle::Progress`] stroke runs around the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PerimeterDirection {
+    #[default]
+    Clockwise,
+    CounterClockwise,
+}
+
 /// Signed-distance-field distance from pixel center `(px, py)` to the nearest point
 /// on the edge of a rounded rectangle.
 ///
@@ -66,6 +157,49 @@ pub(crate) fn rrect_sdf(
     f32::hypot(qx.max(0.0), qy.max(0.0)) + qx.min(0.0).max(qy.min(0.0)) - r
 }
 
+/// Arc-length position of the point `(x, y)` (relative to the centre) projected
+/// onto the outline of a rounded rect with half-extents `(hw, hh)` and corner
+/// radius `r`, and the outline's total length.
+///
+/// Positions start at the middle of the top edge and increase clockwise on
+/// screen, wrapping at the total length.
+pub(crate) fn rrect_perimeter_pos(x: f32, y: f32, hw: f32, hh: f32, r: f32) -> (f32, f32) {
+    let r = r.clamp(0.0, hw.min(hh));
+    // Half-extents of the straight edges, and the length of one corner arc.
+    let (ax, ay) = (hw - r, hh - r);
+    let arc = r * FRAC_PI_2;
+    let total = 4.0 * (ax + ay) + 4.0 * arc;
+
+    let (qx, qy) = (x.clamp(-ax, ax), y.clamp(-ay, ay));
+    let (out_x, out_y) = (x != qx, y != qy);
+    let s = if out_x && out_y {
+        // Corner arc: measure by the angle around the corner centre, turned so
+        // the top-right corner starts at zero.
+        let theta = (y - qy).atan2(x - qx); // −π..π, 0 along +x
+        let (base, start) = match (x > 0.0, y > 0.0) {
+            (true, false) => (ax, -FRAC_PI_2),
+            (true, true) => (ax + arc + 2.0 * ay, 0.0),
+            (false, true) => (3.0 * ax + 2.0 * arc + 2.0 * ay, FRAC_PI_2),
+            (false, false) => (3.0 * ax + 3.0 * arc + 4.0 * ay, PI),
+        };
+        base + r * (theta - start).rem_euclid(TAU).min(FRAC_PI_2)
+    } else {
+        // Straight edge: the one the point is outside of, or else the nearest.
+        let top_or_bottom = if out_x || out_y {
+            out_y
+        } else {
+            ay - y.abs() < ax - x.abs()
+        };
+        match (top_or_bottom, x > 0.0, y > 0.0) {
+            (true, _, false) => x,
+            (false, true, _) => ax + arc + (y + ay),
+            (true, _, true) => ax + 2.0 * arc + 2.0 * ay + (ax - x),
+            (false, false, _) => 3.0 * ax + 3.0 * arc + 2.0 * ay + (ay - y),
+        }
+    };
+    (s.rem_euclid(total), total)
+}
+
 /// Cubic smoothstep: maps `t` from `[edge0, edge1]` to `[0, 1]` with smooth ends.
 #[inline]
 pub(crate) fn smoothstep(edge0: f32, edge1: f32, t: f32) -> f32 {
@@ -100,6 +234,22 @@ mod tests {
         assert!(d.abs() < 1.0, "edge should be near zero (d={d})");
     }
 
+    #[test]
+    fn perimeter_runs_clockwise_from_top_centre() {
+        // 20×10 half-extents, radius 4: edges of 32 and 12, arcs of 2π.
+        let p = |x, y| rrect_perimeter_pos(x, y, 20.0, 10.0, 4.0);
+        let total = 4.0 * 16.0 + 4.0 * 6.0 + 8.0 * PI;
+        assert!((p(0.0, -10.0).1 - total).abs() < 1e-3);
+        assert!(p(0.0, -10.0).0.abs() < 1e-3);
+        assert!((p(10.0, -10.0).0 - 10.0).abs() < 1e-3);
+        // Middle of the right edge: top half, one arc, half the side.
+        assert!((p(20.0, 0.0).0 - (16.0 + 2.0 * PI + 6.0)).abs() < 1e-3);
+        // Halfway round is the bottom centre.
+        assert!((p(0.0, 10.0).0 - total / 2.0).abs() < 1e-3);
+        // Just left of the top centre is almost all the way round.
+        assert!((p(-1.0, -10.0).0 - (total - 1.0)).abs() < 1e-3);
+    }
+
     #[test]
     fn smoothstep_clamps() {
         assert_eq!(smoothstep(0.0, 1.0, -1.0), 0.0);
@@ -1,13 +1,30 @@
 use ab_glyph::{Font, PxScale, ScaleFont};
-use image::{ImageBuffer, Rgba, RgbaImage};
+use image::RgbaImage;
 
 use crate::{
-    border::{BorderStyle, rrect_sdf, smoothstep},
+    bitmap::{
+        ImageFit, ImageOptions, ImageSource, crop_to_clip, decode, place, resize, sample_bilinear,
+    },
+    blend::BlendMode,
+    border::{BorderStyle, PerimeterDirection, rrect_perimeter_pos, rrect_sdf, smoothstep},
     color::Color,
+    effect::Effect,
+    emoji,
     error::RenderError,
+    filter::Filter,
     font::FontHandle,
-    layout::TextLine,
+    geom::{Corner, CornerRadii, Insets, Point, Rect},
+    layout::{TextDirection, TextLine},
+    mask::{Mask, MaskMode},
     output::RenderedImage,
+    paint::Paint,
+    path::{FLATTEN_TOLERANCE, FillRule, Path, flatten, flatten_with_tolerance},
+    pipeline::{Pipeline, Surface},
+    raster::{Coverage, rasterize},
+    shape::{LineCap, ellipse_sdf, rrect_radii_sdf, sdf_coverage, segment_sdf},
+    shaping::{Glyph, shape},
+    stroke::{Stroke, stroke_polygons},
+    transform::Transform,
 };
 
 /// Vertical alignment of the text block within the canvas.
@@ -31,6 +48,67 @@ pub enum HAlign {
     #[default]
     Center,
     Right,
+    /// Left in left-to-right paragraphs, right in right-to-left ones.
+    Start,
+    /// Right in left-to-right paragraphs, left in right-to-left ones.
+    End,
+}
+
+impl HAlign {
+    /// Share of the free width placed before a line in a `direction` paragraph.
+    pub(crate) fn fraction(self, direction: TextDirection) -> f32 {
+        let rtl = direction == TextDirection::Rtl;
+        match self {
+            HAlign::Left => 0.0,
+            HAlign::Center => 0.5,
+            HAlign::Right => 1.0,
+            HAlign::Start if rtl => 1.0,
+            HAlign::End if !rtl => 1.0,
+            HAlign::Start | HAlign::End => 0.0,
+        }
+    }
+}
+
+/// Which point of a text block [`Canvas::draw_text_at`] places at the given
+/// coordinates. The horizontal part also aligns the lines within the block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+    /// On the first baseline, at the left edge.
+    BaselineLeft,
+    /// On the first baseline, centered.
+    Baseline,
+    /// On the first baseline, at the right edge.
+    BaselineRight,
+}
+
+impl Anchor {
+    /// Line alignment, and the anchor's fraction of the block's height from the
+    /// top (`None` for the baseline anchors).
+    fn split(self) -> (HAlign, Option<f32>) {
+        use Anchor::*;
+        let h = match self {
+            TopLeft | Left | BottomLeft | BaselineLeft => HAlign::Left,
+            Top | Center | Bottom | Baseline => HAlign::Center,
+            TopRight | Right | BottomRight | BaselineRight => HAlign::Right,
+        };
+        let v = match self {
+            TopLeft | Top | TopRight => Some(0.0),
+            Left | Center | Right => Some(0.5),
+            BottomLeft | Bottom | BottomRight => Some(1.0),
+            BaselineLeft | Baseline | BaselineRight => None,
+        };
+        (h, v)
+    }
 }
 
 /// Options controlling how text is rendered onto the canvas.
@@ -39,12 +117,30 @@ pub struct TextOptions {
     pub font: FontHandle,
     /// Font size in pixels (passed as `PxScale`).
     pub size: f32,
-    pub color: Color,
+    /// Glyph fill: a solid [`Color`] or a gradient [`Paint`].
+    pub color: Paint,
     pub h_align: HAlign,
     pub v_align: VAlign,
     /// Extra pixels of vertical spacing added between lines on top of the
     /// font's natural line gap.
     pub line_gap: f32,
+    /// Blend mode for this draw. `None` uses the canvas's current blend mode.
+    pub blend_mode: Option<BlendMode>,
+    /// Shadows and glows rendered from the glyphs, in order.
+    pub effects: Vec<Effect>,
+    /// Outline traced along the glyph contours. It is drawn beneath the fill, so
+    /// around filled text only its outer half shows as a halo.
+    pub stroke: Option<Stroke>,
+    /// Paint for the outline.
+    pub stroke_color: Paint,
+    /// Fill the glyphs. Turn off with a `stroke` for hollow text.
+    pub fill: bool,
+    /// Space kept clear inside the area the text is aligned in: the canvas for
+    /// [`Canvas::draw_text`], the rectangle for [`Canvas::draw_text_in`].
+    pub padding: Insets,
+    /// Paragraph direction for bidirectional text and [`HAlign::Start`]/[`HAlign::End`].
+    /// `None` takes it from the first strong character in the lines.
+    pub direction: Option<TextDirection>,
 }
 
 impl TextOptions {
@@ -53,15 +149,22 @@ impl TextOptions {
         Self {
             font,
             size,
-            color: Color::WHITE,
+            color: Paint::Solid(Color::WHITE),
             h_align: HAlign::Center,
             v_align: VAlign::Center,
             line_gap: 0.0,
+            blend_mode: None,
+            effects: Vec::new(),
+            stroke: None,
+            stroke_color: Paint::Solid(Color::BLACK),
+            fill: true,
+            padding: Insets::default(),
+            direction: None,
         }
     }
 
-    pub fn color(mut self, c: Color) -> Self {
-        self.color = c;
+    pub fn color(mut self, c: impl Into<Paint>) -> Self {
+        self.color = c.into();
         self
     }
 
@@ -79,6 +182,42 @@ impl TextOptions {
         self.line_gap = g;
         self
     }
+
+    pub fn blend_mode(mut self, mode: BlendMode) -> Self {
+        self.blend_mode = Some(mode);
+        self
+    }
+
+    /// Pass an `f32` for even padding or [`Insets`] for per-side padding.
+    pub fn padding(mut self, p: impl Into<Insets>) -> Self {
+        self.padding = p.into();
+        self
+    }
+
+    pub fn direction(mut self, d: TextDirection) -> Self {
+        self.direction = Some(d);
+        self
+    }
+
+    /// Outline the glyphs with `stroke` in `color`. Width, joins and dashes come
+    /// from the [`Stroke`]; round joins suit most fonts.
+    pub fn stroke(mut self, stroke: Stroke, color: impl Into<Paint>) -> Self {
+        self.stroke = Some(stroke);
+        self.stroke_color = color.into();
+        self
+    }
+
+    /// Draw only the outline: shorthand for disabling `fill`.
+    pub fn hollow(mut self) -> Self {
+        self.fill = false;
+        self
+    }
+
+    /// Add a shadow or glow. Can be called repeatedly to stack effects.
+    pub fn effect(mut self, effect: Effect) -> Self {
+        self.effects.push(effect);
+        self
+    }
 }
 
 /// An RGBA canvas for compositing text and border effects.
@@ -87,16 +226,54 @@ impl TextOptions {
 /// then call methods to render content, and finally call [`Canvas::finish`] to get the
 /// encoded [`RenderedImage`].
 pub struct Canvas {
-    buf: RgbaImage,
+    buf: Surface,
     width: u32,
     height: u32,
+    blend_mode: BlendMode,
+    /// Clip stack. Each entry is the full-canvas coverage of the intersection of
+    /// every clip pushed so far, so only the top needs consulting.
+    clips: Vec<Vec<f32>>,
+    /// Open layers, innermost last. `buf` always holds the active drawing target.
+    layers: Vec<Layer>,
+    /// Current transform from drawing coordinates to canvas pixels, and its
+    /// inverse (`None` when the transform is singular and nothing is visible).
+    ctm: Transform,
+    inverse: Option<Transform>,
+    /// States stored by [`Canvas::save`], innermost last.
+    saved: Vec<SavedState>,
+}
+
+/// Drawing state captured by [`Canvas::save`].
+struct SavedState {
+    transform: Transform,
+    blend_mode: BlendMode,
+    clip_depth: usize,
+}
+
+/// A group pushed with [`Canvas::push_layer`]: the buffer it will be composited
+/// back onto, and how.
+struct Layer {
+    parent: Surface,
+    opacity: f32,
+    blend_mode: BlendMode,
+    /// The canvas blend mode in effect when the layer was pushed.
+    saved_mode: BlendMode,
 }
 
 impl Canvas {
     /// Create a new transparent canvas of the given dimensions.
     pub fn new(width: u32, height: u32) -> Self {
-        let buf = ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 0]));
-        Self { buf, width, height }
+        Self {
+            buf: Surface::new(width, height, Pipeline::default()),
+            width,
+            height,
+            blend_mode: BlendMode::SourceOver,
+            clips: Vec::new(),
+            layers: Vec::new(),
+            ctm: Transform::IDENTITY,
+            inverse: Some(Transform::IDENTITY),
+            saved: Vec::new(),
+        }
     }
 
     /// 144×144 high-DPI Stream Deck key icon (recommended for modern hardware).
@@ -117,247 +294,1478 @@ impl Canvas {
         self.height
     }
 
-    /// Fill the entire canvas with a solid color.
+    /// The blend mode used by subsequent draws.
+    pub fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    /// Set the blend mode for all subsequent draws: text, borders, shapes, paths
+    /// and images. Draws whose options carry their own `blend_mode` override it.
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// Run `f` with `mode` as the blend mode, restoring the previous mode afterwards.
+    pub fn with_blend_mode<R>(&mut self, mode: BlendMode, f: impl FnOnce(&mut Self) -> R) -> R {
+        let prev = std::mem::replace(&mut self.blend_mode, mode);
+        let out = f(self);
+        self.blend_mode = prev;
+        out
+    }
+
+    pub fn pipeline(&self) -> Pipeline {
+        self.buf.pipeline()
+    }
+
+    /// Switch how pixels are stored and blended. Content already drawn, including
+    /// open layers, is converted. Use [`Pipeline::Legacy`] for output
+    /// byte-identical with earlier releases.
+    pub fn set_pipeline(&mut self, pipeline: Pipeline) {
+        if pipeline == self.pipeline() {
+            return;
+        }
+        let convert = |s: &Surface| Surface::from_image(s.to_image(), pipeline);
+        self.buf = convert(&self.buf);
+        for layer in &mut self.layers {
+            layer.parent = convert(&layer.parent);
+        }
+    }
+
+    /// Remember the current transform, blend mode and clip stack so they can be
+    /// brought back with [`Canvas::restore`]. Calls nest.
+    pub fn save(&mut self) {
+        self.saved.push(SavedState {
+            transform: self.ctm,
+            blend_mode: self.blend_mode,
+            clip_depth: self.clips.len(),
+        });
+    }
+
+    /// Return to the state of the matching [`Canvas::save`], popping any clips
+    /// pushed since. Does nothing when there is no saved state.
+    pub fn restore(&mut self) {
+        if let Some(state) = self.saved.pop() {
+            self.set_transform(state.transform);
+            self.blend_mode = state.blend_mode;
+            self.clips.truncate(state.clip_depth);
+        }
+    }
+
+    /// The current transform from drawing coordinates to canvas pixels.
+    pub fn transform(&self) -> Transform {
+        self.ctm
+    }
+
+    /// Replace the current transform.
+    ///
+    /// Text, shapes, paths, images, SVGs, gradients and clip shapes are all drawn
+    /// through it. `fill`, borders, `draw_horizontal_line` and masks always work in
+    /// canvas pixels.
+    pub fn set_transform(&mut self, t: Transform) {
+        self.ctm = t;
+        self.inverse = t.invert();
+    }
+
+    pub fn reset_transform(&mut self) {
+        self.set_transform(Transform::IDENTITY);
+    }
+
+    /// Apply `t` to drawing coordinates before the current transform.
+    pub fn concat(&mut self, t: Transform) {
+        self.set_transform(self.ctm.then(t));
+    }
+
+    pub fn translate(&mut self, dx: f32, dy: f32) {
+        self.concat(Transform::translate(dx, dy));
+    }
+
+    /// Rotate by `angle` radians around the current origin, clockwise on screen.
+    pub fn rotate(&mut self, angle: f32) {
+        self.concat(Transform::rotate(angle));
+    }
+
+    pub fn scale(&mut self, sx: f32, sy: f32) {
+        self.concat(Transform::scale(sx, sy));
+    }
+
+    /// Shear by `ax` and `ay` radians along the x and y axes.
+    pub fn skew(&mut self, ax: f32, ay: f32) {
+        self.concat(Transform::skew(ax, ay));
+    }
+
+    /// Restrict subsequent draws to `rect` until the matching [`Canvas::pop_clip`].
+    ///
+    /// Clips nest: each push intersects with the clips already active.
+    pub fn push_clip_rect(&mut self, rect: Rect) {
+        self.push_clip_rounded_rect(rect, CornerRadii::default());
+    }
+
+    /// Restrict subsequent draws to a rounded rectangle, e.g. the key shape.
+    pub fn push_clip_rounded_rect(&mut self, rect: Rect, radii: impl Into<CornerRadii>) {
+        let radii = radii.into();
+        let sdf = |x, y| rrect_radii_sdf(x, y, &rect, &radii);
+        let data = (0..self.height)
+            .flat_map(|py| (0..self.width).map(move |px| (px, py)))
+            .map(|(px, py)| self.sdf_coverage_at(px, py, &sdf))
+            .collect();
+        self.push_clip(data);
+    }
+
+    /// Restrict subsequent draws to the interior of `path`, e.g. a circular avatar.
+    pub fn push_clip_path(&mut self, path: &Path, rule: FillRule) {
+        let cov = rasterize(&self.path_polygons(path), rule, self.width, self.height);
+        let mut data = vec![0.0; (self.width * self.height) as usize];
+        if let Some(cov) = cov {
+            for (px, py, c) in cov.iter() {
+                data[(py as u32 * self.width + px as u32) as usize] = c;
+            }
+        }
+        self.push_clip(data);
+    }
+
+    /// Restrict subsequent draws to the opaque (or, inverted, transparent)
+    /// parts of `mask`.
+    pub fn push_clip_mask(&mut self, mask: &Mask, mode: MaskMode) {
+        let data = (0..self.height)
+            .flat_map(|py| (0..self.width).map(move |px| mask.value(px, py, mode)))
+            .collect();
+        self.push_clip(data);
+    }
+
+    /// Remove the most recently pushed clip. Does nothing when no clip is active.
+    pub fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
+    /// Multiply the alpha of everything already drawn by `mask`, within the
+    /// current clip. With [`MaskMode::Inverted`] the mask's shape is cut out.
+    pub fn apply_mask(&mut self, mask: &Mask, mode: MaskMode) {
+        self.with_blend_mode(BlendMode::DestinationIn, |c| {
+            for py in 0..c.height {
+                for px in 0..c.width {
+                    let keep = (mask.value(px, py, mode) * 255.0).round() as u8;
+                    c.blend_pixel(px, py, Color::rgba(0, 0, 0, keep), 1.0);
+                }
+            }
+        });
+    }
+
+    /// Start an offscreen layer. Everything drawn until the matching
+    /// [`Canvas::pop_layer`] is composited back as one unit with `opacity` and
+    /// `blend_mode`, so overlapping elements fade together instead of showing
+    /// through each other.
+    ///
+    /// Draws inside the layer start with [`BlendMode::SourceOver`]; the canvas
+    /// blend mode is restored when the layer is popped. Clips stay in effect.
+    pub fn push_layer(&mut self, opacity: f32, blend_mode: BlendMode) {
+        let fresh = self.buf.blank();
+        self.layers.push(Layer {
+            parent: std::mem::replace(&mut self.buf, fresh),
+            opacity: opacity.clamp(0.0, 1.0),
+            blend_mode,
+            saved_mode: std::mem::replace(&mut self.blend_mode, BlendMode::SourceOver),
+        });
+    }
+
+    /// Composite the innermost layer onto the one below it. Does nothing when
+    /// no layer is open.
+    pub fn pop_layer(&mut self) {
+        let Some(layer) = self.layers.pop() else {
+            return;
+        };
+        let group = std::mem::replace(&mut self.buf, layer.parent);
+        self.blend_mode = layer.saved_mode;
+        if layer.opacity <= 0.0 {
+            return;
+        }
+        self.buf
+            .composite(&group, layer.blend_mode, |_| layer.opacity);
+    }
+
+    /// Run `f` inside a layer, as [`Canvas::push_layer`] … [`Canvas::pop_layer`].
+    pub fn with_layer<R>(
+        &mut self,
+        opacity: f32,
+        blend_mode: BlendMode,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.push_layer(opacity, blend_mode);
+        let out = f(self);
+        self.pop_layer();
+        out
+    }
+
+    /// Run `f` and composite everything it draws with `effects`: drop shadows
+    /// and outer glows behind it, inner shadows on top.
+    ///
+    /// The effects are computed from a blurred copy of the drawn alpha, so they
+    /// work for text, shapes, paths and images alike. Active clips apply to the
+    /// result as a whole.
+    pub fn with_effects<R>(&mut self, effects: &[Effect], f: impl FnOnce(&mut Self) -> R) -> R {
+        if effects.is_empty() {
+            return f(self);
+        }
+
+        let clips = std::mem::take(&mut self.clips);
+        let depth = self.layers.len();
+        self.push_layer(1.0, BlendMode::SourceOver);
+        let out = f(self);
+        while self.layers.len() > depth + 1 {
+            self.pop_layer();
+        }
+        let layer = if self.layers.len() > depth {
+            self.layers.pop()
+        } else {
+            None
+        };
+        let Some(layer) = layer else {
+            // `f` popped the effect layer itself, so its drawing has already
+            // been composited plainly; there is nothing left to apply effects to.
+            self.clips = clips;
+            return out;
+        };
+        let group = std::mem::replace(&mut self.buf, layer.parent);
+        self.blend_mode = layer.saved_mode;
+        self.clips = clips;
+
+        let (w, h) = (self.width as usize, self.height as usize);
+        let alpha = group.alpha();
+        let (inner, outer): (Vec<&Effect>, Vec<&Effect>) =
+            effects.iter().partition(|e| e.is_inner());
+
+        for effect in outer {
+            self.composite_mask(&effect.mask(&alpha, w, h), effect.color());
+        }
+        let clip = self.clips.last();
+        self.buf.composite(&group, self.blend_mode, |i| match clip {
+            _ if alpha[i] <= 0.0 => 0.0,
+            Some(clip) => clip[i],
+            None => 1.0,
+        });
+        for effect in inner {
+            self.composite_mask(&effect.mask(&alpha, w, h), effect.color());
+        }
+        out
+    }
+
+    /// Apply `filter` to everything drawn so far — or, inside a layer, to the
+    /// layer's contents.
+    pub fn apply_filter(&mut self, filter: &Filter) {
+        let full = Rect::new(0.0, 0.0, self.width as f32, self.height as f32);
+        self.apply_filter_in(full, filter);
+    }
+
+    /// Apply `filter` to the pixels inside `rect`, given in canvas pixels.
+    ///
+    /// Blurs only sample inside the region, so edges don't pull in what's around
+    /// it — a frosted-glass backdrop for a label is a blurred region. Active clips
+    /// limit where the result is applied.
+    pub fn apply_filter_in(&mut self, rect: Rect, filter: &Filter) {
+        let x0 = rect.x.round().clamp(0.0, self.width as f32) as u32;
+        let y0 = rect.y.round().clamp(0.0, self.height as f32) as u32;
+        let x1 = rect.right().round().clamp(0.0, self.width as f32) as u32;
+        let y1 = rect.bottom().round().clamp(0.0, self.height as f32) as u32;
+
+        let before = (!self.clips.is_empty()).then(|| self.buf.clone());
+        self.buf.apply_filter(filter, (x0, y0, x1, y1));
+
+        // Blend filtered pixels back over the originals by clip coverage.
+        if let (Some(before), Some(clip)) = (before, self.clips.last()) {
+            for py in y0..y1 {
+                for px in x0..x1 {
+                    let i = (py * self.width + px) as usize;
+                    if clip[i] < 1.0 {
+                        self.buf.mix_from(&before, i, clip[i]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run `f` in a layer, apply `filters` to what it drew, then composite the
+    /// result — e.g. to blur or desaturate one icon without touching the background.
+    pub fn with_filters<R>(&mut self, filters: &[Filter], f: impl FnOnce(&mut Self) -> R) -> R {
+        let depth = self.layers.len();
+        self.push_layer(1.0, BlendMode::SourceOver);
+        let out = f(self);
+        while self.layers.len() > depth + 1 {
+            self.pop_layer();
+        }
+        // Filter the whole layer; clips already shaped what was drawn into it.
+        let clips = std::mem::take(&mut self.clips);
+        for filter in filters {
+            self.apply_filter(filter);
+        }
+        self.clips = clips;
+        self.pop_layer();
+        out
+    }
+
+    /// Fill the entire canvas with a solid color or gradient, replacing its contents.
     ///
     /// Use [`Color::TRANSPARENT`] to reset to a fully transparent background.
-    pub fn fill(&mut self, color: Color) {
-        for pixel in self.buf.pixels_mut() {
-            *pixel = Rgba([color.r, color.g, color.b, color.a]);
+    /// With a clip active only the clipped area is replaced.
+    pub fn fill(&mut self, paint: impl Into<Paint>) {
+        let paint = paint.into();
+        if !self.clips.is_empty() {
+            self.with_identity(|c| {
+                c.with_blend_mode(BlendMode::Source, |c| {
+                    for py in 0..c.height {
+                        for px in 0..c.width {
+                            c.paint_pixel(px, py, &paint, 1.0);
+                        }
+                    }
+                })
+            });
+            return;
+        }
+        for py in 0..self.height {
+            for px in 0..self.width {
+                self.buf.put(px, py, paint.color_at(px as i32, py as i32));
+            }
         }
     }
 
     /// Render pre-wrapped lines of text onto the canvas.
     ///
     /// Lines are laid out according to `opts.h_align` and `opts.v_align`.
-    /// Glyphs are composited using Porter-Duff "source over destination".
+    /// Glyphs are composited with `opts.blend_mode`, or the canvas's current
+    /// blend mode when it is `None`.
     pub fn draw_text(&mut self, lines: &[TextLine], opts: &TextOptions) -> Result<(), RenderError> {
-        if lines.is_empty() {
-            return Ok(());
-        }
+        let full = Rect::new(0.0, 0.0, self.width as f32, self.height as f32);
+        self.draw_text_in(full, lines, opts).map(|_| ())
+    }
 
-        let scale = PxScale::from(opts.size);
-        let sf = opts.font.arc().as_scaled(scale);
+    /// Render text aligned within `rect` instead of the whole canvas, inset by
+    /// `opts.padding`. `VAlign::Baseline` stays an absolute canvas coordinate.
+    ///
+    /// Returns the rectangle the text block occupies — line advances by line
+    /// heights, grown by any outline — so follow-on content can be placed
+    /// relative to it.
+    pub fn draw_text_in(
+        &mut self,
+        rect: Rect,
+        lines: &[TextLine],
+        opts: &TextOptions,
+    ) -> Result<Rect, RenderError> {
+        let area = rect.inset_by(opts.padding);
+        self.draw_text_block(area, opts.h_align, opts.v_align, lines, opts)
+    }
 
-        let ascent = sf.ascent();
-        let descent = sf.descent(); // negative
-        let font_line_gap = sf.line_gap();
-        let line_h = ascent - descent + font_line_gap + opts.line_gap;
+    /// Render text with its `anchor` point at `(x, y)`: [`Anchor::TopLeft`]
+    /// starts the block there, [`Anchor::Baseline`] centers the first baseline
+    /// on it. Alignment and padding from `opts` are not used.
+    ///
+    /// Returns the rectangle the text block occupies, as [`Canvas::draw_text_in`].
+    pub fn draw_text_at(
+        &mut self,
+        x: f32,
+        y: f32,
+        anchor: Anchor,
+        lines: &[TextLine],
+        opts: &TextOptions,
+    ) -> Result<Rect, RenderError> {
+        let (h_align, v_frac) = anchor.split();
+        let width = lines.iter().map(|l| l.width_px).fold(0.0, f32::max);
+        let left = x - width * h_align.fraction(TextDirection::Ltr);
+        let (top, v_align) = match v_frac {
+            Some(f) => (y - text_metrics(lines, opts).total_h * f, VAlign::Top),
+            None => (y, VAlign::Baseline(y)),
+        };
+        let area = Rect::new(left, top, width, 0.0);
+        self.draw_text_block(area, h_align, v_align, lines, opts)
+    }
 
-        let n = lines.len() as f32;
-        // Total height of the text block: N lines of ascent+descent, (N-1) gaps.
-        let total_h = ascent - descent + (n - 1.0) * line_h;
+    /// Lay out and render `lines` aligned within `area`.
+    fn draw_text_block(
+        &mut self,
+        area: Rect,
+        h_align: HAlign,
+        v_align: VAlign,
+        lines: &[TextLine],
+        opts: &TextOptions,
+    ) -> Result<Rect, RenderError> {
+        if lines.is_empty() {
+            return Ok(Rect::new(area.x, area.y, 0.0, 0.0));
+        }
+        if let Some(mode) = opts.blend_mode {
+            return self.with_blend_mode(mode, |c| {
+                let opts = TextOptions {
+                    blend_mode: None,
+                    ..opts.clone()
+                };
+                c.draw_text_block(area, h_align, v_align, lines, &opts)
+            });
+        }
+        if !opts.effects.is_empty() {
+            return self.with_effects(&opts.effects, |c| {
+                let opts = TextOptions {
+                    effects: Vec::new(),
+                    ..opts.clone()
+                };
+                c.draw_text_block(area, h_align, v_align, lines, &opts)
+            });
+        }
 
-        let w = self.width as f32;
-        let h = self.height as f32;
+        let direction = opts
+            .direction
+            .or_else(|| lines.iter().find_map(|l| TextDirection::detect(&l.text)))
+            .unwrap_or_default();
+        let align = h_align.fraction(direction);
+        let TextMetrics {
+            ascent,
+            descent,
+            line_h,
+            total_h,
+        } = text_metrics(lines, opts);
 
         // Y of the first baseline.
-        let first_baseline_y = match opts.v_align {
-            VAlign::Top => ascent,
-            VAlign::Center => (h - total_h) / 2.0 + ascent,
-            VAlign::Bottom => h - (total_h - ascent),
+        let first_baseline_y = match v_align {
+            VAlign::Top => area.y + ascent,
+            VAlign::Center => area.y + (area.height - total_h) / 2.0 + ascent,
+            VAlign::Bottom => area.bottom() - (total_h - ascent),
             VAlign::Baseline(y) => y,
         };
 
-        for (i, line) in lines.iter().enumerate() {
-            let baseline_y = first_baseline_y + i as f32 * line_h;
+        let origins: Vec<Point> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let start_x = area.x + (area.width - line.width_px) * align;
+                Point::new(start_x, first_baseline_y + i as f32 * line_h)
+            })
+            .collect();
 
-            let start_x = match opts.h_align {
-                HAlign::Left => 0.0,
-                HAlign::Center => (w - line.width_px) / 2.0,
-                HAlign::Right => w - line.width_px,
-            };
+        // Stroke every line before filling any, so outlines never cover glyphs.
+        if let Some(stroke) = &opts.stroke {
+            let outline = lines
+                .iter()
+                .zip(&origins)
+                .flat_map(|(line, o)| {
+                    shape(&opts.font, opts.size, &line.text, Some(direction)).placed(o.x, o.y)
+                })
+                .filter_map(|(glyph, origin)| match glyph {
+                    Glyph::Font { face, id } => opts.font.glyph_path(face, id, opts.size, origin),
+                    Glyph::Image(_) => None,
+                })
+                .fold(Path::new(), Path::append);
+            self.stroke_path(&outline, stroke, opts.stroke_color.clone());
+        }
 
-            draw_text_line(
-                &mut self.buf,
-                &line.text,
-                &opts.font,
-                scale,
-                start_x,
-                baseline_y,
-                opts.color,
-                self.width,
-                self.height,
-            );
+        if opts.fill {
+            for (line, o) in lines.iter().zip(&origins) {
+                self.draw_text_line(
+                    &line.text,
+                    direction,
+                    &opts.font,
+                    opts.size,
+                    *o,
+                    &opts.color,
+                );
+            }
         }
 
-        Ok(())
+        let bounds = lines
+            .iter()
+            .zip(&origins)
+            .map(|(line, o)| Rect::from_ltrb(o.x, o.y - ascent, o.x + line.width_px, o.y - descent))
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default();
+        let outset = opts.stroke.as_ref().map_or(0.0, |s| s.width / 2.0);
+        Ok(bounds.inset(-outset))
     }
 
     /// Draw a rounded-rectangle border effect over the canvas.
     ///
     /// Uses the SDF from [`crate::border::rrect_sdf`] for smooth anti-aliasing.
     pub fn draw_border(&mut self, style: &BorderStyle) {
-        match style {
-            BorderStyle::None => {}
-            BorderStyle::Solid { thickness, radius, color } => {
-                self.draw_solid_border(*thickness, *radius, *color);
-            }
-            BorderStyle::Vignette { width, radius, color } => {
-                self.draw_vignette_border(*width, *radius, *color);
-            }
+        if !self.ctm.is_identity() {
+            return self.with_identity(|c| c.draw_border(style));
         }
+        let frame = Rect::new(0.0, 0.0, self.width as f32, self.height as f32);
+        self.draw_border_in(frame, style);
     }
 
     /// Draw a 1px horizontal line across the canvas at pixel row `y`.
     ///
     /// Useful for separators in multi-section button layouts.
-    pub fn draw_horizontal_line(&mut self, y: u32, color: Color) {
+    pub fn draw_horizontal_line(&mut self, y: u32, paint: impl Into<Paint>) {
         if y >= self.height {
             return;
         }
-        for px in 0..self.width {
-            let pixel = self.buf.get_pixel_mut(px, y);
-            composite_over(pixel, color, color.a as f32 / 255.0);
+        let paint = paint.into();
+        self.with_identity(|c| {
+            for px in 0..c.width {
+                c.paint_pixel(px, y, &paint, 1.0);
+            }
+        });
+    }
+
+    /// Fill an axis-aligned rectangle.
+    pub fn fill_rect(&mut self, rect: Rect, paint: impl Into<Paint>) {
+        self.fill_rounded_rect(rect, CornerRadii::default(), paint);
+    }
+
+    /// Stroke the outline of an axis-aligned rectangle.
+    ///
+    /// The stroke is centered on the rectangle edge, so half of `thickness` lies
+    /// outside `rect`.
+    pub fn stroke_rect(&mut self, rect: Rect, thickness: f32, paint: impl Into<Paint>) {
+        self.stroke_rounded_rect(rect, CornerRadii::default(), thickness, paint);
+    }
+
+    /// Fill a rounded rectangle. Pass an `f32` for uniform corners or a
+    /// [`CornerRadii`] for per-corner radii.
+    pub fn fill_rounded_rect(
+        &mut self,
+        rect: Rect,
+        radii: impl Into<CornerRadii>,
+        paint: impl Into<Paint>,
+    ) {
+        let radii = radii.into();
+        self.fill_sdf(rect, &paint.into(), |x, y| {
+            rrect_radii_sdf(x, y, &rect, &radii)
+        });
+    }
+
+    /// Stroke the outline of a rounded rectangle, centered on its edge.
+    pub fn stroke_rounded_rect(
+        &mut self,
+        rect: Rect,
+        radii: impl Into<CornerRadii>,
+        thickness: f32,
+        paint: impl Into<Paint>,
+    ) {
+        let radii = radii.into();
+        let half_t = thickness / 2.0;
+        self.fill_sdf(rect.inset(-half_t), &paint.into(), |x, y| {
+            rrect_radii_sdf(x, y, &rect, &radii).abs() - half_t
+        });
+    }
+
+    /// Fill a circle centered at `center`.
+    pub fn fill_circle(&mut self, center: Point, radius: f32, paint: impl Into<Paint>) {
+        self.fill_ellipse(center, radius, radius, paint);
+    }
+
+    /// Stroke a circle outline of the given `thickness`, centered on `radius`.
+    pub fn stroke_circle(
+        &mut self,
+        center: Point,
+        radius: f32,
+        thickness: f32,
+        paint: impl Into<Paint>,
+    ) {
+        let half_t = thickness / 2.0;
+        let bounds = Rect::new(
+            center.x - radius,
+            center.y - radius,
+            radius * 2.0,
+            radius * 2.0,
+        );
+        self.fill_sdf(bounds.inset(-half_t), &paint.into(), |x, y| {
+            (f32::hypot(x - center.x, y - center.y) - radius).abs() - half_t
+        });
+    }
+
+    /// Fill an axis-aligned ellipse with horizontal radius `rx` and vertical radius `ry`.
+    pub fn fill_ellipse(&mut self, center: Point, rx: f32, ry: f32, paint: impl Into<Paint>) {
+        if rx <= 0.0 || ry <= 0.0 {
+            return;
         }
+        let bounds = Rect::new(center.x - rx, center.y - ry, rx * 2.0, ry * 2.0);
+        self.fill_sdf(bounds, &paint.into(), |x, y| {
+            ellipse_sdf(x, y, center.x, center.y, rx, ry)
+        });
+    }
+
+    /// Draw a straight line between two arbitrary points.
+    ///
+    /// `thickness` is the full stroke width; `cap` controls how the ends are drawn.
+    pub fn draw_line(
+        &mut self,
+        from: Point,
+        to: Point,
+        thickness: f32,
+        cap: LineCap,
+        paint: impl Into<Paint>,
+    ) {
+        let half_t = thickness / 2.0;
+        let bounds = Rect::from_ltrb(
+            from.x.min(to.x),
+            from.y.min(to.y),
+            from.x.max(to.x),
+            from.y.max(to.y),
+        );
+        self.fill_sdf(bounds.inset(-half_t), &paint.into(), |x, y| {
+            segment_sdf(x, y, from, to, half_t, cap)
+        });
+    }
+
+    /// Fill the interior of a vector path using the given fill rule.
+    ///
+    /// Open subpaths are implicitly closed for filling.
+    pub fn fill_path(&mut self, path: &Path, rule: FillRule, paint: impl Into<Paint>) {
+        let polygons = self.path_polygons(path);
+        if let Some(cov) = rasterize(&polygons, rule, self.width, self.height) {
+            self.fill_coverage(&cov, &paint.into());
+        }
+    }
+
+    /// Stroke the outline of a vector path with the given width, caps, joins and dashes.
+    pub fn stroke_path(&mut self, path: &Path, stroke: &Stroke, paint: impl Into<Paint>) {
+        let polygons = self.stroke_outline(path, stroke);
+        if let Some(cov) = rasterize(&polygons, FillRule::NonZero, self.width, self.height) {
+            self.fill_coverage(&cov, &paint.into());
+        }
+    }
+
+    /// Draw a raster image into `dest`, sized according to `opts.fit`.
+    ///
+    /// Accepts encoded bytes, a base64 string or decoded pixels (see [`ImageSource`]).
+    /// Pixels that fall outside `dest` are clipped.
+    pub fn draw_image<'a>(
+        &mut self,
+        src: impl Into<ImageSource<'a>>,
+        dest: Rect,
+        opts: &ImageOptions,
+    ) -> Result<(), RenderError> {
+        if let Some(mode) = opts.blend_mode {
+            let opts = ImageOptions {
+                blend_mode: None,
+                ..opts.clone()
+            };
+            return self.with_blend_mode(mode, |c| c.draw_image(src, dest, &opts));
+        }
+
+        let img = decode(src.into())?;
+        if img.width() == 0 || img.height() == 0 || dest.width <= 0.0 || dest.height <= 0.0 {
+            return Ok(());
+        }
+
+        // Only the part inside `dest`, the canvas and the clip is drawn; don't
+        // resample the rest.
+        let Some(visible) = self.visible_part(dest) else {
+            return Ok(());
+        };
+        let placed = place(img.width() as f32, img.height() as f32, &dest, opts.fit);
+        let (img, placed) = crop_to_clip(img, placed, &visible);
+        // Resample to the size the image will cover on the canvas.
+        let s = self.ctm.mean_scale();
+        let tw = (placed.width * s).round().max(1.0) as u32;
+        let th = (placed.height * s).round().max(1.0) as u32;
+        // The crop keeps whole source pixels, so a hugely magnified image can
+        // still spill far past the visible area; sample it directly instead.
+        if tw as f32 > (visible.width * s).ceil() * 2.0 + 2.0
+            || th as f32 > (visible.height * s).ceil() * 2.0 + 2.0
+        {
+            self.blit_transformed(&img, &placed, &dest, opts.opacity);
+            return Ok(());
+        }
+        let scaled = if (tw, th) == img.dimensions() {
+            img
+        } else {
+            std::borrow::Cow::Owned(resize(&img, tw, th, opts.quality))
+        };
+
+        self.blit(&scaled, &placed, &dest, opts.opacity);
+        Ok(())
+    }
+
+    /// Rasterize an SVG document into `dest`, sized according to `opts.fit`.
+    ///
+    /// The document is rendered at the final pixel size, so icons stay sharp at
+    /// any key resolution. Requires the `svg` cargo feature.
+    #[cfg(feature = "svg")]
+    pub fn draw_svg(
+        &mut self,
+        svg: &[u8],
+        dest: Rect,
+        opts: &crate::svg::SvgOptions,
+    ) -> Result<(), RenderError> {
+        if let Some(mode) = opts.blend_mode {
+            let opts = crate::svg::SvgOptions {
+                blend_mode: None,
+                ..opts.clone()
+            };
+            return self.with_blend_mode(mode, |c| c.draw_svg(svg, dest, &opts));
+        }
+
+        let tree = crate::svg::parse(svg, opts)?;
+        let size = tree.size();
+        if dest.width <= 0.0 || dest.height <= 0.0 {
+            return Ok(());
+        }
+
+        let Some(visible) = self.visible_part(dest) else {
+            return Ok(());
+        };
+        let placed = place(size.width(), size.height(), &dest, opts.fit);
+        let Some(area) = placed.intersect(visible) else {
+            return Ok(());
+        };
+        // Render at the size the document covers on the canvas, but only the
+        // whole pixels of it that can show.
+        let s = self.ctm.mean_scale();
+        let tw = (placed.width * s).round().max(1.0) as u32;
+        let th = (placed.height * s).round().max(1.0) as u32;
+        let (kx, ky) = (tw as f32 / placed.width, th as f32 / placed.height);
+        let edge = |v: f32, max: u32| v.clamp(0.0, max as f32) as u32;
+        let x0 = edge(((area.x - placed.x) * kx).floor(), tw);
+        let y0 = edge(((area.y - placed.y) * ky).floor(), th);
+        let x1 = edge(((area.right() - placed.x) * kx).ceil(), tw).max(x0 + 1);
+        let y1 = edge(((area.bottom() - placed.y) * ky).ceil(), th).max(y0 + 1);
+        let Some(img) = crate::svg::render(&tree, tw, th, (x0, y0, x1 - x0, y1 - y0)) else {
+            return Ok(());
+        };
+        let window = Rect::new(
+            placed.x + x0 as f32 / kx,
+            placed.y + y0 as f32 / ky,
+            (x1 - x0) as f32 / kx,
+            (y1 - y0) as f32 / ky,
+        );
+        self.blit(&img, &window, &dest, opts.opacity);
+        Ok(())
     }
 
     /// Consume the canvas and return a [`RenderedImage`] ready for encoding.
-    pub fn finish(self) -> RenderedImage {
-        RenderedImage { buf: self.buf }
+    ///
+    /// Any layers still open are composited first.
+    pub fn finish(mut self) -> RenderedImage {
+        while !self.layers.is_empty() {
+            self.pop_layer();
+        }
+        RenderedImage {
+            buf: self.buf.to_image(),
+        }
     }
 
     // ── private helpers ─────────────────────────────────────────────────────
 
-    fn draw_solid_border(&mut self, thickness: f32, radius: f32, color: Color) {
-        let w = self.width as f32;
-        let h = self.height as f32;
-        let cx = w / 2.0;
-        let cy = h / 2.0;
-        let hw = w / 2.0;
-        let hh = h / 2.0;
+    /// Alpha of everything drawn so far in `[0, 1]`, row-major.
+    pub(crate) fn alpha(&self) -> Vec<f32> {
+        self.buf.alpha()
+    }
 
+    /// Push a clip with per-pixel coverage `data` (row-major, canvas-sized),
+    /// intersected with the current clip.
+    fn push_clip(&mut self, mut data: Vec<f32>) {
+        for c in &mut data {
+            *c = c.clamp(0.0, 1.0);
+        }
+        if let Some(top) = self.clips.last() {
+            for (c, outer) in data.iter_mut().zip(top) {
+                *c *= outer;
+            }
+        }
+        self.clips.push(data);
+    }
+
+    /// Composite `color` over the whole canvas, weighted by per-pixel `coverage`.
+    fn composite_mask(&mut self, coverage: &[f32], color: Color) {
         for py in 0..self.height {
             for px in 0..self.width {
-                let fx = px as f32 + 0.5;
-                let fy = py as f32 + 0.5;
-                let dist = rrect_sdf(fx, fy, cx, cy, hw, hh, radius);
+                let c = coverage[(py * self.width + px) as usize];
+                if c > 0.0 {
+                    self.blend_pixel(px, py, color, c);
+                }
+            }
+        }
+    }
+
+    /// Run `f` with the identity transform, for draws defined in canvas pixels.
+    fn with_identity<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let prev = self.ctm;
+        self.reset_transform();
+        let out = f(self);
+        self.set_transform(prev);
+        out
+    }
+
+    /// Flatten `path` into canvas-pixel polygons under the current transform.
+    fn path_polygons(&self, path: &Path) -> Vec<Vec<Point>> {
+        let polylines = if self.ctm.is_identity() {
+            flatten(path)
+        } else {
+            flatten(&path.clone().transform(self.ctm))
+        };
+        polylines.into_iter().map(|p| p.points).collect()
+    }
+
+    /// Stroke outline of `path` in canvas pixels. Stroking happens in drawing
+    /// coordinates, so widths and dashes scale with the transform.
+    fn stroke_outline(&self, path: &Path, stroke: &Stroke) -> Vec<Vec<Point>> {
+        if self.ctm.is_identity() {
+            return stroke_polygons(&flatten(path), stroke);
+        }
+        let tolerance = FLATTEN_TOLERANCE / self.ctm.mean_scale().max(1e-3);
+        let mut polygons = stroke_polygons(&flatten_with_tolerance(path, tolerance), stroke);
+        for p in polygons.iter_mut().flatten() {
+            *p = self.ctm.map_point(*p);
+        }
+        polygons
+    }
+
+    /// Canvas pixel range `(x0, y0, x1, y1)` covering `bounds` (in drawing
+    /// coordinates) after the transform, grown by 1px for the AA fringe.
+    fn device_bounds(&self, bounds: Rect) -> (u32, u32, u32, u32) {
+        let bounds = self.ctm.map_rect(bounds);
+        let x0 = (bounds.x - 1.0).floor().max(0.0) as u32;
+        let y0 = (bounds.y - 1.0).floor().max(0.0) as u32;
+        let x1 = (bounds.right() + 1.0).ceil().clamp(0.0, self.width as f32) as u32;
+        let y1 = (bounds.bottom() + 1.0)
+            .ceil()
+            .clamp(0.0, self.height as f32) as u32;
+        (x0, y0, x1, y1)
+    }
 
-                // Paint the band [−thickness, 0] (from inner edge to outer edge).
-                // AA: 1px smooth transition on each side.
-                let outer_aa = smoothstep(1.0, 0.0, dist);           // 1 just inside, 0 outside
-                let inner_aa = smoothstep(-thickness - 1.0, -thickness, dist); // 0 deep inside, 1 at inner edge
+    /// Anti-aliased coverage of canvas pixel `(px, py)` by the shape whose signed
+    /// distance field, in drawing coordinates, is `sdf`.
+    ///
+    /// Under a transform the distance is rescaled by the field's gradient in canvas
+    /// pixels, so edges stay one pixel soft whatever the scale or skew.
+    fn sdf_coverage_at(&self, px: u32, py: u32, sdf: &impl Fn(f32, f32) -> f32) -> f32 {
+        let (x, y) = (px as f32 + 0.5, py as f32 + 0.5);
+        if self.ctm.is_identity() {
+            return sdf_coverage(sdf(x, y));
+        }
+        let Some(inv) = self.inverse else {
+            return 0.0;
+        };
+        let p = inv.map_point(Point::new(x, y));
+        let d = sdf(p.x, p.y);
+        let gx = sdf(p.x + inv.a, p.y + inv.b) - d;
+        let gy = sdf(p.x + inv.c, p.y + inv.d) - d;
+        sdf_coverage(d / gx.hypot(gy).max(1e-6))
+    }
 
-                let alpha = outer_aa * inner_aa * (color.a as f32 / 255.0);
-                if alpha > 0.0 {
-                    let pixel = self.buf.get_pixel_mut(px, py);
-                    composite_over(pixel, color, alpha);
+    /// Rasterize a single line of text at the given baseline position.
+    pub(crate) fn draw_text_line(
+        &mut self,
+        text: &str,
+        direction: TextDirection,
+        font: &FontHandle,
+        size: f32,
+        start: Point,
+        paint: &Paint,
+    ) {
+        let glyphs = shape(font, size, text, Some(direction)).placed(start.x, start.y);
+        for (glyph, origin) in glyphs {
+            let (face, glyph_id) = match glyph {
+                Glyph::Font { face, id } => (face, id),
+                Glyph::Image(img) => {
+                    let rect = emoji::image_box(font, size, origin);
+                    let opts = ImageOptions::new().fit(ImageFit::Fill);
+                    // Already decoded, so this can't fail.
+                    let _ = self.draw_image(&*img, rect, &opts);
+                    continue;
                 }
+            };
+            if font.is_color_face(face)
+                && self.draw_color_glyph(font, face, glyph_id, size, origin, paint)
+            {
+                continue;
+            }
+
+            // Under a transform, fill the glyph outline as a path.
+            if !self.ctm.is_identity() {
+                if let Some(path) = font.glyph_path(face, glyph_id, size, origin) {
+                    let polygons = self.path_polygons(&path);
+                    if let Some(cov) =
+                        rasterize(&polygons, FillRule::NonZero, self.width, self.height)
+                    {
+                        self.fill_coverage(&cov, paint);
+                    }
+                }
+                continue;
+            }
+
+            let glyph = glyph_id.with_scale_and_position(
+                font.face_scale(face, size),
+                ab_glyph::point(origin.x, origin.y),
+            );
+            if let Some(og) = font.face(face).outline_glyph(glyph) {
+                let bounds = og.px_bounds();
+                og.draw(|dx, dy, coverage| {
+                    let px = bounds.min.x as i32 + dx as i32;
+                    let py = bounds.min.y as i32 + dy as i32;
+                    if px >= 0 && py >= 0 && (px as u32) < self.width && (py as u32) < self.height {
+                        let cov = coverage.clamp(0.0, 1.0);
+                        if cov > 0.0 {
+                            self.paint_pixel(px as u32, py as u32, paint, cov);
+                        }
+                    }
+                });
             }
         }
     }
 
-    fn draw_vignette_border(&mut self, width: f32, radius: f32, color: Color) {
-        let w = self.width as f32;
-        let h = self.height as f32;
-        let cx = w / 2.0;
-        let cy = h / 2.0;
-        let hw = w / 2.0;
-        let hh = h / 2.0;
+    /// Composite a pre-scaled image at the top-left of `placed`, clipped to `clip`.
+    fn blit(&mut self, img: &RgbaImage, placed: &Rect, clip: &Rect, opacity: f32) {
+        if !self.ctm.is_identity() {
+            return self.blit_transformed(img, placed, clip, opacity);
+        }
+        let ox = placed.x.round() as i64;
+        let oy = placed.y.round() as i64;
+        let opacity = opacity.clamp(0.0, 1.0);
 
-        let peak_alpha = color.a as f32 / 255.0;
+        for (ix, iy, p) in img.enumerate_pixels() {
+            let px = ox + ix as i64;
+            let py = oy + iy as i64;
+            if px < 0 || py < 0 || px >= self.width as i64 || py >= self.height as i64 {
+                continue;
+            }
+            let center = Point::new(px as f32 + 0.5, py as f32 + 0.5);
+            if !clip.contains(center) {
+                continue;
+            }
+            let color = Color::rgba(p[0], p[1], p[2], p[3]);
+            self.blend_pixel(px as u32, py as u32, color, opacity);
+        }
+    }
 
-        for py in 0..self.height {
-            for px in 0..self.width {
-                let fx = px as f32 + 0.5;
-                let fy = py as f32 + 0.5;
-                let dist = rrect_sdf(fx, fy, cx, cy, hw, hh, radius);
+    /// The part of `rect`, in drawing coordinates, that can reach the canvas:
+    /// inside its bounds and the active clip. `None` when nothing would show.
+    fn visible_part(&self, rect: Rect) -> Option<Rect> {
+        let inv = self.inverse?;
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut device = Rect::new(0.0, 0.0, w as f32, h as f32);
+        if let Some(clip) = self.clips.last() {
+            let (mut x0, mut y0, mut x1, mut y1) = (w, h, 0, 0);
+            for (i, _) in clip.iter().enumerate().filter(|(_, c)| **c > 0.0) {
+                let (x, y) = (i % w, i / w);
+                (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1));
+            }
+            let bounds = Rect::from_ltrb(x0 as f32, y0 as f32, x1 as f32, y1 as f32);
+            device = device.intersect(bounds)?;
+        }
+        rect.intersect(inv.map_rect(device))
+    }
+
+    /// [`Canvas::blit`] under a transform: `img` spans `placed` in drawing
+    /// coordinates and is sampled bilinearly, with anti-aliased edges.
+    fn blit_transformed(&mut self, img: &RgbaImage, placed: &Rect, clip: &Rect, opacity: f32) {
+        let Some(inv) = self.inverse else {
+            return;
+        };
+        let area = Rect::from_ltrb(
+            placed.x.max(clip.x),
+            placed.y.max(clip.y),
+            placed.right().min(clip.right()),
+            placed.bottom().min(clip.bottom()),
+        );
+        if area.width <= 0.0 || area.height <= 0.0 {
+            return;
+        }
+        let sx = img.width() as f32 / placed.width;
+        let sy = img.height() as f32 / placed.height;
+        let opacity = opacity.clamp(0.0, 1.0);
+        let sdf = |x, y| rrect_radii_sdf(x, y, &area, &CornerRadii::default());
 
-                // Only pixels inside the shape (dist < 0) are painted.
-                if dist >= 1.0 {
+        let (x0, y0, x1, y1) = self.device_bounds(area);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let coverage = self.sdf_coverage_at(px, py, &sdf);
+                if coverage <= 0.0 {
                     continue;
                 }
+                let p = inv.map_point(Point::new(px as f32 + 0.5, py as f32 + 0.5));
+                let color = sample_bilinear(
+                    img,
+                    (p.x - placed.x) * sx - 0.5,
+                    (p.y - placed.y) * sy - 0.5,
+                );
+                self.blend_pixel(px, py, color, coverage * opacity);
+            }
+        }
+    }
 
-                // inset: how far inside the boundary we are (0 at edge, positive inside)
-                let inset = -dist;
-                // Clamp to vignette width
-                if inset > width {
-                    continue;
+    /// Composite `paint` over pixel `(px, py)`, scaled by `coverage` in `[0, 1]`.
+    ///
+    /// Gradients are sampled in drawing coordinates, so they follow the transform.
+    fn paint_pixel(&mut self, px: u32, py: u32, paint: &Paint, coverage: f32) {
+        let color = match self.inverse {
+            Some(inv) if !self.ctm.is_identity() => {
+                let p = inv.map_point(Point::new(px as f32 + 0.5, py as f32 + 0.5));
+                paint.color_at_point(p.x, p.y, px as i32, py as i32)
+            }
+            _ => paint.color_at(px as i32, py as i32),
+        };
+        self.blend_pixel(px, py, color, coverage);
+    }
+
+    /// Composite a single `color` over pixel `(px, py)` with the current blend
+    /// mode, scaled by `coverage` and the active clip.
+    fn blend_pixel(&mut self, px: u32, py: u32, color: Color, coverage: f32) {
+        let coverage = match self.clips.last() {
+            Some(clip) => coverage * clip[(py * self.width + px) as usize],
+            None => coverage,
+        };
+        if coverage > 0.0 {
+            self.buf.blend(px, py, color, coverage, self.blend_mode);
+        }
+    }
+
+    /// Composite `paint` over every pixel covered by `bounds` (in drawing
+    /// coordinates), weighted by the coverage of the signed distance field `sdf`.
+    fn fill_sdf(&mut self, bounds: Rect, paint: &Paint, sdf: impl Fn(f32, f32) -> f32) {
+        let (x0, y0, x1, y1) = self.device_bounds(bounds);
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let coverage = self.sdf_coverage_at(px, py, &sdf);
+                if coverage > 0.0 {
+                    self.paint_pixel(px, py, paint, coverage);
                 }
+            }
+        }
+    }
 
-                // Outer shape AA (handles the 1px anti-aliased edge of the rounded rect itself)
-                let shape_aa = smoothstep(1.0, 0.0, dist);
+    /// Composite `paint` over every pixel of a rasterized coverage patch.
+    fn fill_coverage(&mut self, cov: &Coverage, paint: &Paint) {
+        for (px, py, coverage) in cov.iter() {
+            self.paint_pixel(px as u32, py as u32, paint, coverage);
+        }
+    }
 
-                // Quadratic falloff: bright at edge (t=0), zero at width (t=1)
-                let t = (inset / width).clamp(0.0, 1.0);
-                let falloff = (1.0 - t) * (1.0 - t);
+    /// Draw `style` along the edge of `frame`.
+    fn draw_border_in(&mut self, frame: Rect, style: &BorderStyle) {
+        match style {
+            BorderStyle::None => {}
+            BorderStyle::Solid {
+                thickness,
+                radius,
+                color,
+            } => {
+                let t = *thickness;
+                self.paint_border(frame, *radius, color, |dist, _, _| band(dist, 0.0, t));
+            }
+            BorderStyle::Vignette {
+                width,
+                radius,
+                color,
+            } => {
+                self.paint_border(frame, *radius, color, |dist, _, _| {
+                    // inset: how far inside the boundary we are (0 at edge, positive inside)
+                    let inset = -dist;
+                    if dist >= 1.0 || inset > *width {
+                        return 0.0;
+                    }
+                    // Outer shape AA times a quadratic falloff: bright at the
+                    // edge (t=0), zero at width (t=1). The paint's own alpha
+                    // sets the peak.
+                    let t = (inset / width).clamp(0.0, 1.0);
+                    smoothstep(1.0, 0.0, dist) * (1.0 - t) * (1.0 - t)
+                });
+            }
+            BorderStyle::Dashed {
+                thickness,
+                radius,
+                dash,
+                gap,
+                color,
+            } => {
+                let t = *thickness;
+                let line = PerimeterLine::new(frame, *radius, t / 2.0);
+                let (period, dash) = line.fit(*dash, *gap);
+                self.paint_border(frame, *radius, color, |dist, x, y| {
+                    // Centre the first dash on the top edge's midpoint.
+                    let phase = (line.pos(x, y) + dash / 2.0).rem_euclid(period);
+                    let d = if phase < dash {
+                        phase.min(dash - phase)
+                    } else {
+                        -(phase - dash).min(period - phase)
+                    };
+                    band(dist, 0.0, t) * (d + 0.5).clamp(0.0, 1.0)
+                });
+            }
+            BorderStyle::Dotted {
+                size,
+                radius,
+                gap,
+                color,
+            } => {
+                let r = size / 2.0;
+                let line = PerimeterLine::new(frame, *radius, r);
+                let (period, _) = line.fit(*size, *gap);
+                self.paint_border(frame, *radius, color, |dist, x, y| {
+                    let phase = line.pos(x, y).rem_euclid(period);
+                    let along = phase.min(period - phase);
+                    sdf_coverage(along.hypot(dist + r) - r)
+                });
+            }
+            BorderStyle::Double {
+                thickness,
+                gap,
+                radius,
+                color,
+            } => {
+                let t = *thickness;
+                self.paint_border(frame, *radius, color, |dist, _, _| {
+                    band(dist, 0.0, t) + band(dist, t + gap, t)
+                });
+            }
+            BorderStyle::Progress {
+                thickness,
+                radius,
+                fraction,
+                start,
+                direction,
+                cap,
+                color,
+            } => {
+                let t = *thickness;
+                let line = PerimeterLine::new(frame, *radius, t / 2.0);
+                let filled = fraction.clamp(0.0, 1.0) * line.length;
+                if filled <= 0.0 {
+                    return;
+                }
+                if filled >= line.length {
+                    self.paint_border(frame, *radius, color, |dist, _, _| band(dist, 0.0, t));
+                    return;
+                }
+                let (sx, sy) = match start {
+                    Corner::TopLeft => (-line.hw, -line.hh),
+                    Corner::TopRight => (line.hw, -line.hh),
+                    Corner::BottomLeft => (-line.hw, line.hh),
+                    Corner::BottomRight => (line.hw, line.hh),
+                };
+                let origin = line.pos(sx, sy);
+                let end_ext = match cap {
+                    LineCap::Butt => 0.0,
+                    LineCap::Round | LineCap::Square => t / 2.0,
+                };
+                self.paint_border(frame, *radius, color, |dist, x, y| {
+                    let s = line.pos(x, y) - origin;
+                    let along = match direction {
+                        PerimeterDirection::Clockwise => s,
+                        PerimeterDirection::CounterClockwise => -s,
+                    }
+                    .rem_euclid(line.length);
+                    // Distance along the line to the nearest end: positive
+                    // inside the stroke, negative past its end or before its start.
+                    let d = if along <= filled {
+                        along.min(filled - along)
+                    } else {
+                        -(along - filled).min(line.length - along)
+                    };
+                    match cap {
+                        LineCap::Round if d < 0.0 => {
+                            sdf_coverage(d.hypot(dist + t / 2.0) - t / 2.0)
+                        }
+                        _ => band(dist, 0.0, t) * (d + end_ext + 0.5).clamp(0.0, 1.0),
+                    }
+                });
+            }
+            BorderStyle::Inset { inset, style } => {
+                self.draw_border_in(frame.inset(*inset), style);
+            }
+        }
+    }
 
-                let alpha = peak_alpha * falloff * shape_aa;
-                if alpha > 0.0 {
-                    let pixel = self.buf.get_pixel_mut(px, py);
-                    composite_over(pixel, color, alpha);
+    /// Paint the pixels around the edge of the rounded rect `frame` with
+    /// `coverage(dist, x, y)`: the pixel centre's signed distance to the edge
+    /// (negative inside) and its position relative to the frame's centre.
+    fn paint_border(
+        &mut self,
+        frame: Rect,
+        radius: f32,
+        paint: &Paint,
+        coverage: impl Fn(f32, f32, f32) -> f32,
+    ) {
+        let Point { x: cx, y: cy } = frame.center();
+        let (hw, hh) = (frame.width / 2.0, frame.height / 2.0);
+        if hw <= 0.0 || hh <= 0.0 {
+            return;
+        }
+        let (x0, y0, x1, y1) = self.device_bounds(frame.inset(-1.0));
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let fx = px as f32 + 0.5;
+                let fy = py as f32 + 0.5;
+                let dist = rrect_sdf(fx, fy, cx, cy, hw, hh, radius);
+                let c = coverage(dist, fx - cx, fy - cy).min(1.0);
+                if c > 0.0 {
+                    self.paint_pixel(px, py, paint, c);
                 }
             }
         }
     }
 }
 
-/// Porter-Duff "source over destination" compositing.
-///
-/// `src_alpha` is the pre-multiplied effective alpha of the source (already in `[0,1]`).
-#[inline]
-fn composite_over(dst: &mut Rgba<u8>, src_color: Color, src_alpha: f32) {
-    let dst_a = dst[3] as f32 / 255.0;
-    let out_a = src_alpha + dst_a * (1.0 - src_alpha);
-
-    if out_a <= 0.0 {
-        return;
-    }
-
-    let blend = |src_c: u8, dst_c: u8| -> u8 {
-        let s = src_c as f32 / 255.0;
-        let d = dst_c as f32 / 255.0;
-        ((s * src_alpha + d * dst_a * (1.0 - src_alpha)) / out_a * 255.0).round() as u8
-    };
-
-    dst[0] = blend(src_color.r, dst[0]);
-    dst[1] = blend(src_color.g, dst[1]);
-    dst[2] = blend(src_color.b, dst[2]);
-    dst[3] = (out_a * 255.0).round() as u8;
+/// Coverage of the band `[-from - thickness, -from]` of signed distances, with
+/// 1px anti-aliasing on each side.
+fn band(dist: f32, from: f32, thickness: f32) -> f32 {
+    let outer_aa = smoothstep(1.0 - from, -from, dist); // 1 just inside, 0 outside
+    let inner_aa = smoothstep(-from - thickness - 1.0, -from - thickness, dist); // 0 deep inside
+    outer_aa * inner_aa
+}
+
+/// The centre line of a border band, `offset` pixels in from a frame's edge,
+/// for measuring positions along it.
+struct PerimeterLine {
+    hw: f32,
+    hh: f32,
+    radius: f32,
+    length: f32,
+}
+
+impl PerimeterLine {
+    fn new(frame: Rect, radius: f32, offset: f32) -> Self {
+        let hw = (frame.width / 2.0 - offset).max(0.0);
+        let hh = (frame.height / 2.0 - offset).max(0.0);
+        let radius = (radius - offset).max(0.0);
+        let (_, length) = rrect_perimeter_pos(0.0, 0.0, hw, hh, radius);
+        Self {
+            hw,
+            hh,
+            radius,
+            length,
+        }
+    }
+
+    /// Arc-length position of the point `(x, y)` relative to the centre.
+    fn pos(&self, x: f32, y: f32) -> f32 {
+        rrect_perimeter_pos(x, y, self.hw, self.hh, self.radius).0
+    }
+
+    /// Stretch a `mark` + `gap` pattern so a whole number of repeats fits the
+    /// line: the adjusted period and mark length.
+    fn fit(&self, mark: f32, gap: f32) -> (f32, f32) {
+        let want = (mark + gap).max(1.0);
+        let n = (self.length / want).round().max(1.0);
+        let period = self.length / n;
+        (period, mark * period / want)
+    }
+}
+
+/// Vertical metrics of a block of `lines` at `opts.size`.
+struct TextMetrics {
+    ascent: f32,
+    /// Negative: below the baseline.
+    descent: f32,
+    line_h: f32,
+    /// N lines of ascent + descent with (N − 1) line gaps.
+    total_h: f32,
+}
+
+fn text_metrics(lines: &[TextLine], opts: &TextOptions) -> TextMetrics {
+    let sf = opts.font.arc().as_scaled(PxScale::from(opts.size));
+    let (ascent, descent) = (sf.ascent(), sf.descent());
+    let line_h = ascent - descent + sf.line_gap() + opts.line_gap;
+    let n = lines.len().max(1) as f32;
+    TextMetrics {
+        ascent,
+        descent,
+        line_h,
+        total_h: ascent - descent + (n - 1.0) * line_h,
+    }
 }
 
-/// Rasterize a single line of text into `img` at the given baseline position.
-#[allow(clippy::too_many_arguments)]
-fn draw_text_line(
-    img: &mut RgbaImage,
-    text: &str,
-    font: &FontHandle,
-    scale: PxScale,
-    start_x: f32,
-    baseline_y: f32,
-    color: Color,
-    img_w: u32,
-    img_h: u32,
-) {
-    let sf = font.arc().as_scaled(scale);
-    let mut cursor_x = start_x;
-    let mut prev = None;
-
-    for ch in text.chars() {
-        let glyph_id = sf.glyph_id(ch);
-        if let Some(prev_id) = prev {
-            cursor_x += sf.kern(prev_id, glyph_id);
-        }
-
-        let glyph = glyph_id.with_scale_and_position(
-            scale,
-            ab_glyph::point(cursor_x, baseline_y),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_into_huge_dest_resamples_only_the_canvas() {
+        let img = RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 255]));
+        let huge = Rect::new(-50_000.0, -50_000.0, 100_000.0, 100_000.0);
+        for (fit, quality) in [
+            (ImageFit::Fill, crate::ImageQuality::Bicubic),
+            (ImageFit::Cover, crate::ImageQuality::Nearest),
+        ] {
+            let mut c = Canvas::new(8, 8);
+            let opts = ImageOptions::new().fit(fit).quality(quality);
+            c.draw_image(&img, huge, &opts).unwrap();
+            assert_eq!(c.finish().buf.get_pixel(4, 4).0, [255, 0, 0, 255]);
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn svg_into_huge_dest_renders_only_the_canvas() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 5">
+            <rect width="10" height="5" fill="red"/>
+        </svg>"#;
+        let huge = Rect::new(-50_000.0, -50_000.0, 100_000.0, 100_000.0);
+        let mut c = Canvas::new(8, 8);
+        let opts = crate::svg::SvgOptions::new().fit(ImageFit::Cover);
+        c.draw_svg(svg, huge, &opts).unwrap();
+        assert_eq!(c.finish().buf.get_pixel(4, 4).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn layer_fades_group_as_one() {
+        let mut c = Canvas::new(20, 20);
+        c.with_layer(0.5, BlendMode::SourceOver, |c| {
+            c.fill_rect(Rect::new(0.0, 0.0, 15.0, 20.0), Color::WHITE);
+            c.fill_rect(Rect::new(5.0, 0.0, 15.0, 20.0), Color::WHITE);
+        });
+        let img = c.finish().buf;
+        // The overlap is no more opaque than either rect alone.
+        assert_eq!(img.get_pixel(10, 10)[3], 128);
+        assert_eq!(img.get_pixel(2, 10)[3], 128);
+    }
+
+    #[test]
+    fn layer_blend_mode_applies_on_pop() {
+        let mut c = Canvas::new(4, 4);
+        c.fill(Color::rgb(200, 100, 50));
+        c.push_layer(1.0, BlendMode::Multiply);
+        assert_eq!(c.blend_mode(), BlendMode::SourceOver);
+        c.fill(Color::rgb(128, 128, 128));
+        c.pop_layer();
+        assert_eq!(c.finish().buf.get_pixel(1, 1).0, [100, 50, 25, 255]);
+    }
+
+    #[test]
+    fn effects_survive_closure_popping_extra_layers() {
+        let mut c = Canvas::new(4, 4);
+        c.push_layer(1.0, BlendMode::SourceOver);
+        c.with_effects(&[Effect::drop_shadow(1.0, 1.0, 1.0, Color::BLACK)], |c| {
+            c.fill(Color::WHITE);
+            c.pop_layer();
+            c.pop_layer();
+        });
+        assert_eq!(c.finish().buf.get_pixel(0, 0)[3], 255);
+    }
+
+    #[test]
+    fn finish_closes_open_layers() {
+        let mut c = Canvas::new(4, 4);
+        c.push_layer(1.0, BlendMode::SourceOver);
+        c.fill(Color::WHITE);
+        assert_eq!(c.finish().buf.get_pixel(0, 0)[3], 255);
+    }
+
+    #[test]
+    fn set_pipeline_keeps_drawn_content() {
+        let mut c = Canvas::new(4, 4);
+        c.fill(Color::rgba(200, 100, 50, 128));
+        c.push_layer(1.0, BlendMode::SourceOver);
+        c.set_pipeline(Pipeline::Linear);
+        assert_eq!(c.pipeline(), Pipeline::Linear);
+        c.set_pipeline(Pipeline::Legacy);
+        c.pop_layer();
+        assert_eq!(c.finish().buf.get_pixel(0, 0).0, [200, 100, 50, 128]);
+    }
+
+    #[test]
+    fn float_pipelines_filter_without_rounding() {
+        let draw = |pipeline, filters: &[Filter]| {
+            let mut c = Canvas::new(4, 4);
+            c.set_pipeline(pipeline);
+            c.fill(Color::rgba(200, 100, 50, 200));
+            for f in filters {
+                c.apply_filter_in(Rect::new(1.0, 1.0, 2.0, 2.0), f);
+            }
+            c.finish().buf.get_pixel(1, 1).0
+        };
+        // Darkened below one byte step and back: only float surfaces recover.
+        let dim_and_back = [Filter::Brightness(0.001), Filter::Brightness(1000.0)];
+        for pipeline in [Pipeline::Premultiplied, Pipeline::Linear] {
+            let px = draw(pipeline, &dim_and_back);
+            assert!(
+                px.iter()
+                    .zip([200, 100, 50, 200])
+                    .all(|(a, b)| a.abs_diff(b) <= 1),
+                "{pipeline:?}: {px:?}"
+            );
+            // Per-pixel filters still work in sRGB terms on every pipeline.
+            let gray = draw(pipeline, &[Filter::Grayscale(1.0)]);
+            let legacy = draw(Pipeline::Legacy, &[Filter::Grayscale(1.0)]);
+            assert!(gray.iter().zip(legacy).all(|(a, b)| a.abs_diff(b) <= 1));
+        }
+        assert_eq!(draw(Pipeline::Legacy, &dim_and_back)[0], 0);
+    }
+
+    #[test]
+    fn transform_moves_shapes_and_restore_undoes_it() {
+        let mut c = Canvas::new(20, 20);
+        c.save();
+        c.translate(10.0, 0.0);
+        c.push_clip_rect(Rect::new(0.0, 0.0, 10.0, 10.0));
+        c.fill_rect(Rect::new(0.0, 0.0, 20.0, 20.0), Color::WHITE);
+        c.restore();
+        assert!(c.transform().is_identity());
+        c.fill_rect(Rect::new(0.0, 15.0, 5.0, 5.0), Color::WHITE);
+
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(15, 5)[3], 255);
+        assert_eq!(img.get_pixel(5, 5)[3], 0);
+        assert_eq!(
+            img.get_pixel(15, 15)[3],
+            0,
+            "clip pushed after save is gone"
         );
-        cursor_x += sf.h_advance(glyph_id);
-        prev = Some(glyph_id);
-
-        if let Some(og) = font.arc().outline_glyph(glyph) {
-            let bounds = og.px_bounds();
-            og.draw(|dx, dy, coverage| {
-                let px = bounds.min.x as i32 + dx as i32;
-                let py = bounds.min.y as i32 + dy as i32;
-                if px >= 0 && py >= 0 && (px as u32) < img_w && (py as u32) < img_h {
-                    let cov = coverage.clamp(0.0, 1.0);
-                    if cov > 0.0 {
-                        let pixel = img.get_pixel_mut(px as u32, py as u32);
-                        composite_over(pixel, color, cov * (color.a as f32 / 255.0));
-                    }
-                }
+        assert_eq!(img.get_pixel(2, 17)[3], 255);
+    }
+
+    #[test]
+    fn rotated_rect_covers_rotated_pixels() {
+        let mut c = Canvas::new(20, 20);
+        c.translate(10.0, 10.0);
+        c.rotate(std::f32::consts::FRAC_PI_2);
+        // A wide bar becomes a tall one.
+        c.fill_rect(Rect::new(-8.0, -2.0, 16.0, 4.0), Color::WHITE);
+
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(10, 3)[3], 255);
+        assert_eq!(img.get_pixel(3, 10)[3], 0);
+    }
+
+    #[test]
+    fn progress_border_covers_fraction_from_start_corner() {
+        let draw = |direction| {
+            let mut c = Canvas::new(40, 40);
+            c.draw_border(&BorderStyle::Progress {
+                thickness: 4.0,
+                radius: 0.0,
+                fraction: 0.5,
+                start: Corner::TopLeft,
+                direction,
+                cap: LineCap::Butt,
+                color: Color::WHITE.into(),
             });
-        }
+            let img = c.finish().buf;
+            // Alpha at the middle of the top, right, bottom and left edges.
+            [(20, 1), (38, 20), (20, 38), (1, 20)].map(|(x, y)| img.get_pixel(x, y)[3])
+        };
+        assert_eq!(draw(PerimeterDirection::Clockwise), [255, 255, 0, 0]);
+        assert_eq!(draw(PerimeterDirection::CounterClockwise), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn start_and_end_align_follow_paragraph_direction() {
+        use TextDirection::{Ltr, Rtl};
+        assert_eq!(HAlign::Start.fraction(Ltr), 0.0);
+        assert_eq!(HAlign::Start.fraction(Rtl), 1.0);
+        assert_eq!(HAlign::End.fraction(Ltr), 1.0);
+        assert_eq!(HAlign::End.fraction(Rtl), 0.0);
+        assert_eq!(HAlign::Left.fraction(Rtl), 0.0);
+    }
+
+    #[test]
+    fn start_and_end_align_rtl_text_to_the_right_and_left() {
+        let font = crate::test_fonts::dejavu();
+        let lines = [TextLine {
+            text: "שלום".into(),
+            width_px: crate::layout::measure_line(&font, 20.0, "שלום"),
+        }];
+        // Columns holding any ink, for a paragraph whose direction is detected.
+        let ink = |align: HAlign| {
+            let mut c = Canvas::new(120, 30);
+            let opts = TextOptions::new(font.clone(), 20.0).h_align(align);
+            c.draw_text_in(Rect::new(0.0, 0.0, 120.0, 30.0), &lines, &opts)
+                .unwrap();
+            let img = c.finish().buf;
+            let cols: Vec<u32> = (0..120)
+                .filter(|&x| (0..30).any(|y| img.get_pixel(x, y)[3] > 0))
+                .collect();
+            (cols[0], *cols.last().unwrap())
+        };
+        let (_, start_right) = ink(HAlign::Start);
+        let (end_left, _) = ink(HAlign::End);
+        assert!(start_right >= 116, "start ends at {start_right}");
+        assert!(end_left <= 3, "end begins at {end_left}");
     }
 }
@@ -0,0 +1,321 @@
+use std::collections::VecDeque;
+
+use crate::{color::Color, filter::gaussian_blur};
+
+/// A shadow or glow rendered from the alpha of a text or shape draw.
+///
+/// Request effects with [`crate::TextOptions::effect`] or wrap any draws in
+/// [`crate::Canvas::with_effects`]. Offsets, blur and spread are in canvas pixels;
+/// `blur` is a radius as in CSS `box-shadow` (a Gaussian with σ = blur / 2).
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{Color, Effect};
+///
+/// let opts = TextOptions::new(font, 28.0)
+///     .effect(Effect::drop_shadow(0.0, 2.0, 4.0, Color::rgba(0, 0, 0, 200)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Effect {
+    /// A blurred copy of the shape's silhouette, offset and drawn behind it.
+    DropShadow {
+        dx: f32,
+        dy: f32,
+        blur: f32,
+        /// Grow (positive) or shrink (negative) the silhouette before blurring.
+        spread: f32,
+        color: Color,
+    },
+    /// A blurred halo around the shape, drawn behind it.
+    OuterGlow {
+        blur: f32,
+        spread: f32,
+        color: Color,
+    },
+    /// A shadow cast inside the shape by its own edges, drawn on top of it.
+    InnerShadow {
+        dx: f32,
+        dy: f32,
+        blur: f32,
+        spread: f32,
+        color: Color,
+    },
+}
+
+impl Effect {
+    pub fn drop_shadow(dx: f32, dy: f32, blur: f32, color: Color) -> Self {
+        Self::DropShadow {
+            dx,
+            dy,
+            blur,
+            spread: 0.0,
+            color,
+        }
+    }
+
+    pub fn outer_glow(blur: f32, color: Color) -> Self {
+        Self::OuterGlow {
+            blur,
+            spread: 0.0,
+            color,
+        }
+    }
+
+    pub fn inner_shadow(dx: f32, dy: f32, blur: f32, color: Color) -> Self {
+        Self::InnerShadow {
+            dx,
+            dy,
+            blur,
+            spread: 0.0,
+            color,
+        }
+    }
+
+    /// Set the spread of the effect.
+    pub fn spread(mut self, s: f32) -> Self {
+        match &mut self {
+            Self::DropShadow { spread, .. }
+            | Self::OuterGlow { spread, .. }
+            | Self::InnerShadow { spread, .. } => *spread = s,
+        }
+        self
+    }
+
+    pub fn color(&self) -> Color {
+        match *self {
+            Self::DropShadow { color, .. }
+            | Self::OuterGlow { color, .. }
+            | Self::InnerShadow { color, .. } => color,
+        }
+    }
+
+    /// Whether the effect is drawn on top of the shape rather than behind it.
+    pub(crate) fn is_inner(&self) -> bool {
+        matches!(self, Self::InnerShadow { .. })
+    }
+
+    /// Per-pixel coverage of the effect for a shape with alpha `alpha`
+    /// (`width × height`, row-major, values in `[0, 1]`).
+    pub(crate) fn mask(&self, alpha: &[f32], width: usize, height: usize) -> Vec<f32> {
+        let (dx, dy, blur, spread) = match *self {
+            Self::DropShadow {
+                dx,
+                dy,
+                blur,
+                spread,
+                ..
+            }
+            | Self::InnerShadow {
+                dx,
+                dy,
+                blur,
+                spread,
+                ..
+            } => (dx, dy, blur, spread),
+            Self::OuterGlow { blur, spread, .. } => (0.0, 0.0, blur, spread),
+        };
+
+        // An inner shadow is cast by everything *outside* the shape, which
+        // includes the area beyond the canvas edge.
+        let (source, outside): (Vec<f32>, f32) = if self.is_inner() {
+            (alpha.iter().map(|a| 1.0 - a).collect(), 1.0)
+        } else {
+            (alpha.to_vec(), 0.0)
+        };
+
+        let mut m = shift(&source, width, height, dx, dy, outside);
+        if spread != 0.0 {
+            m = morph(&m, width, height, spread, outside);
+        }
+        gaussian_blur(&mut m, width, height, blur / 2.0, Some(outside));
+
+        if self.is_inner() {
+            for (v, a) in m.iter_mut().zip(alpha) {
+                *v *= a;
+            }
+        }
+        m
+    }
+}
+
+/// Translate `data` by whole pixels, filling uncovered cells with `outside`.
+fn shift(data: &[f32], w: usize, h: usize, dx: f32, dy: f32, outside: f32) -> Vec<f32> {
+    let (dx, dy) = (dx.round() as isize, dy.round() as isize);
+    let mut out = vec![outside; data.len()];
+    for y in 0..h {
+        let sy = y as isize - dy;
+        if sy < 0 || sy >= h as isize {
+            continue;
+        }
+        for x in 0..w {
+            let sx = x as isize - dx;
+            if sx >= 0 && sx < w as isize {
+                out[y * w + x] = data[sy as usize * w + sx as usize];
+            }
+        }
+    }
+    out
+}
+
+/// Dilate (`radius > 0`) or erode (`radius < 0`) with a disc of `|radius|` pixels.
+///
+/// The disc is split into horizontal spans: each row offset takes a sliding
+/// min/max of the matching width, so the cost is `O(w·h·r)` rather than
+/// `O(w·h·r²)`. The radius is capped at the larger canvas side, past which the
+/// result can't change.
+fn morph(data: &[f32], w: usize, h: usize, radius: f32, outside: f32) -> Vec<f32> {
+    if !radius.is_finite() || w == 0 || h == 0 {
+        return data.to_vec();
+    }
+    let grow = radius > 0.0;
+    let pick = |a: f32, b: f32| if grow { a.max(b) } else { a.min(b) };
+    let r = radius.abs().min(w.max(h) as f32);
+
+    let mut out = vec![if grow { 0.0 } else { 1.0 }; data.len()];
+    let mut spans = vec![0.0; data.len()];
+    for oy in 0..=r.floor() as usize {
+        let half = (r * r - (oy * oy) as f32).max(0.0).sqrt().floor() as usize;
+        for (src, dst) in data.chunks_exact(w).zip(spans.chunks_exact_mut(w)) {
+            sliding_extreme(src, half, grow, outside, dst);
+        }
+        for y in 0..h {
+            for sy in [y.checked_sub(oy), Some(y + oy)] {
+                let row = &mut out[y * w..(y + 1) * w];
+                match sy.filter(|&sy| sy < h) {
+                    Some(sy) => {
+                        for (o, &v) in row.iter_mut().zip(&spans[sy * w..(sy + 1) * w]) {
+                            *o = pick(*o, v);
+                        }
+                    }
+                    None => row.iter_mut().for_each(|o| *o = pick(*o, outside)),
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Max (`grow`) or min of `line` over a window of `half` pixels either side,
+/// treating samples past the ends as `outside`.
+fn sliding_extreme(line: &[f32], half: usize, grow: bool, outside: f32, out: &mut [f32]) {
+    let n = line.len() as isize;
+    let k = half as isize;
+    let at = |i: isize| {
+        if i < 0 || i >= n {
+            outside
+        } else {
+            line[i as usize]
+        }
+    };
+    let beats = |a: f32, b: f32| if grow { a >= b } else { a <= b };
+
+    // Monotonic deque of candidate indices; the front is the window's extreme.
+    let mut window = VecDeque::new();
+    for i in -k..n + k {
+        while window.back().is_some_and(|&j| beats(at(i), at(j))) {
+            window.pop_back();
+        }
+        window.push_back(i);
+        let c = i - k;
+        if c >= 0 {
+            while window.front().is_some_and(|&j| j < c - k) {
+                window.pop_front();
+            }
+            out[c as usize] = at(window[0]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(w: usize, h: usize, x0: usize, y0: usize, s: usize) -> Vec<f32> {
+        let mut v = vec![0.0; w * h];
+        for y in y0..y0 + s {
+            for x in x0..x0 + s {
+                v[y * w + x] = 1.0;
+            }
+        }
+        v
+    }
+
+    #[test]
+    fn drop_shadow_is_offset() {
+        let alpha = square(30, 30, 5, 5, 10);
+        let m = Effect::drop_shadow(10.0, 0.0, 0.0, Color::BLACK).mask(&alpha, 30, 30);
+        assert_eq!(m[10 * 30 + 10], 0.0);
+        assert_eq!(m[10 * 30 + 20], 1.0);
+    }
+
+    #[test]
+    fn spread_grows_glow() {
+        let alpha = square(30, 30, 10, 10, 10);
+        let m = Effect::outer_glow(0.0, Color::WHITE)
+            .spread(3.0)
+            .mask(&alpha, 30, 30);
+        assert_eq!(m[15 * 30 + 7], 1.0);
+        assert_eq!(m[15 * 30 + 5], 0.0);
+    }
+
+    #[test]
+    fn morph_matches_disc() {
+        let w = 17;
+        let data: Vec<f32> = (0..w * w).map(|i| ((i * 37) % 11) as f32 / 10.0).collect();
+        for radius in [1.0, 2.5, -3.0, 4.2] {
+            let m = morph(&data, w, w, radius, 0.0);
+            let r: f32 = f32::abs(radius);
+            let ri = r.ceil() as isize;
+            for y in 0..w as isize {
+                for x in 0..w as isize {
+                    let mut want: f32 = if radius > 0.0 { 0.0 } else { 1.0 };
+                    for oy in -ri..=ri {
+                        for ox in -ri..=ri {
+                            if ((ox * ox + oy * oy) as f32) > r * r {
+                                continue;
+                            }
+                            let (sx, sy) = (x + ox, y + oy);
+                            let v = if sx < 0 || sy < 0 || sx >= w as isize || sy >= w as isize {
+                                0.0
+                            } else {
+                                data[sy as usize * w + sx as usize]
+                            };
+                            want = if radius > 0.0 {
+                                want.max(v)
+                            } else {
+                                want.min(v)
+                            };
+                        }
+                    }
+                    assert_eq!(
+                        m[y as usize * w + x as usize],
+                        want,
+                        "r={radius} ({x}, {y})"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn huge_or_infinite_spread_finishes() {
+        let alpha = square(30, 30, 10, 10, 10);
+        let m = Effect::outer_glow(0.0, Color::WHITE)
+            .spread(1e9)
+            .mask(&alpha, 30, 30);
+        assert!(m.iter().all(|&v| v == 1.0));
+        let m = Effect::outer_glow(0.0, Color::WHITE)
+            .spread(f32::INFINITY)
+            .mask(&alpha, 30, 30);
+        assert_eq!(m, alpha);
+    }
+
+    #[test]
+    fn inner_shadow_stays_inside() {
+        let alpha = square(30, 30, 10, 10, 10);
+        let m = Effect::inner_shadow(2.0, 2.0, 2.0, Color::BLACK).mask(&alpha, 30, 30);
+        assert_eq!(m[5 * 30 + 5], 0.0);
+        // Strongest along the top-left edges the offset exposes.
+        assert!(m[10 * 30 + 10] > m[18 * 30 + 18]);
+    }
+}
@@ -0,0 +1,602 @@
+use std::{
+    collections::HashMap,
+    path::{Path as FsPath, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use ab_glyph::{Font, GlyphId, PxScale, ScaleFont};
+use image::RgbaImage;
+use rustybuzz::ttf_parser::{self, RasterImageFormat, colr};
+
+use crate::{
+    bitmap::{ImageFit, ImageOptions, ImageSource, decode},
+    blend::BlendMode,
+    canvas::Canvas,
+    color::Color,
+    error::RenderError,
+    font::FontHandle,
+    geom::{Point, Rect},
+    paint::{ExtendMode, Paint},
+    path::{FillRule, Path},
+    transform::Transform,
+};
+
+const VS16: char = '\u{FE0F}';
+const ZWJ: char = '\u{200D}';
+const KEYCAP: char = '\u{20E3}';
+
+/// A folder of emoji images named by codepoint sequence, Twemoji style:
+/// `1f44d.png`, `1f44d-1f3fd.png` (skin tone), `1f469-200d-1f4bb.png` (ZWJ
+/// sequence), `1f1e9-1f1ea.png` (flag).
+///
+/// Attach it to a font with [`FontHandle::emoji_images`]; emoji found in the
+/// folder are then drawn from it instead of the font, sized to one em.
+/// Names are matched with and without `fe0f` variation selectors, so sets
+/// that drop them work too. Images are decoded on first use and cached.
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::EmojiDirectory;
+///
+/// let twemoji = EmojiDirectory::open("assets/twemoji/72x72")?;
+/// let font = fonts.require("sans")?.emoji_images(&twemoji);
+/// ```
+#[derive(Clone)]
+pub struct EmojiDirectory {
+    inner: Arc<DirectoryInner>,
+}
+
+struct DirectoryInner {
+    dir: PathBuf,
+    /// File stem (lowercase hex codepoints joined by `-`) → file.
+    files: HashMap<String, PathBuf>,
+    cache: Mutex<HashMap<String, Option<Arc<RgbaImage>>>>,
+}
+
+impl EmojiDirectory {
+    /// Index the `.png` files in `dir`. Nothing is decoded until it is drawn.
+    pub fn open(dir: impl AsRef<FsPath>) -> Result<Self, RenderError> {
+        let dir = dir.as_ref();
+        let io_err = |source| RenderError::EmojiDirIo {
+            path: dir.display().to_string(),
+            source,
+        };
+        let mut files = HashMap::new();
+        for entry in std::fs::read_dir(dir).map_err(io_err)? {
+            let path = entry.map_err(io_err)?.path();
+            let is_png = path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("png"));
+            if let (true, Some(stem)) = (is_png, path.file_stem().and_then(|s| s.to_str())) {
+                files.insert(stem.to_ascii_lowercase(), path.clone());
+            }
+        }
+        Ok(Self {
+            inner: Arc::new(DirectoryInner {
+                dir: dir.to_path_buf(),
+                files,
+                cache: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// The image for emoji sequence `seq`, if the folder has one that decodes.
+    pub(crate) fn lookup(&self, seq: &str) -> Option<Arc<RgbaImage>> {
+        let key = file_key(seq);
+        let mut cache = self.inner.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache
+            .entry(key)
+            .or_insert_with_key(|key| {
+                let stripped = key
+                    .split('-')
+                    .filter(|&c| c != "fe0f")
+                    .collect::<Vec<_>>()
+                    .join("-");
+                let path = self
+                    .inner
+                    .files
+                    .get(key)
+                    .or_else(|| self.inner.files.get(&stripped))?;
+                let img = image::open(path).ok()?.into_rgba8();
+                Some(Arc::new(img))
+            })
+            .clone()
+    }
+}
+
+impl std::fmt::Debug for EmojiDirectory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmojiDirectory")
+            .field("dir", &self.inner.dir)
+            .field("images", &self.inner.files.len())
+            .finish()
+    }
+}
+
+/// Twemoji file name (without extension) for an emoji sequence.
+fn file_key(seq: &str) -> String {
+    seq.chars()
+        .map(|c| format!("{:x}", c as u32))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Byte length of the emoji sequence at the start of `s`, if there is one:
+/// an emoji with its variation selector and skin-tone modifier, a keycap, a
+/// flag, or several of those joined with ZWJ, plus any tag sequence.
+pub(crate) fn sequence_len(s: &str) -> Option<usize> {
+    let mut len = element_len(s)?;
+    while let Some(rest) = s[len..].strip_prefix(ZWJ) {
+        match element_len(rest) {
+            Some(n) => len += ZWJ.len_utf8() + n,
+            None => break,
+        }
+    }
+    Some(len)
+}
+
+/// Byte length of one emoji (without ZWJ joins) at the start of `s`.
+fn element_len(s: &str) -> Option<usize> {
+    let mut chars = s.chars().peekable();
+    let first = chars.next()?;
+    let mut len = first.len_utf8();
+    let mut take = |len: &mut usize, pred: &dyn Fn(char) -> bool| match chars.peek() {
+        Some(&c) if pred(c) => {
+            *len += c.len_utf8();
+            chars.next();
+            true
+        }
+        _ => false,
+    };
+
+    if is_regional_indicator(first) {
+        take(&mut len, &is_regional_indicator);
+        return Some(len);
+    }
+    if matches!(first, '0'..='9' | '#' | '*') {
+        take(&mut len, &|c| c == VS16);
+        return take(&mut len, &|c| c == KEYCAP).then_some(len);
+    }
+    if !is_emoji_base(first) {
+        return None;
+    }
+    // A text presentation selector asks for the plain symbol.
+    if take(&mut len, &|c| c == '\u{FE0E}') {
+        return None;
+    }
+    take(&mut len, &|c| c == VS16);
+    take(&mut len, &is_skin_tone);
+    // Subdivision flags: tag characters ending in CANCEL TAG.
+    while take(&mut len, &|c| ('\u{E0020}'..='\u{E007E}').contains(&c)) {}
+    take(&mut len, &|c| c == '\u{E007F}');
+    Some(len)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn is_skin_tone(c: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
+
+/// Characters that can start an emoji. Deliberately broad: anything not in
+/// the emoji folder or a color font still renders as plain text.
+fn is_emoji_base(c: char) -> bool {
+    matches!(c,
+        '\u{A9}' | '\u{AE}' | '\u{203C}' | '\u{2049}' | '\u{2122}' | '\u{2139}'
+        | '\u{2194}'..='\u{21AA}'
+        | '\u{231A}'..='\u{23FF}'
+        | '\u{24C2}'
+        | '\u{25AA}'..='\u{25FE}'
+        | '\u{2600}'..='\u{27BF}'
+        | '\u{2934}' | '\u{2935}'
+        | '\u{2B05}'..='\u{2B55}'
+        | '\u{3030}' | '\u{303D}' | '\u{3297}' | '\u{3299}'
+        | '\u{1F000}'..='\u{1FAFF}')
+}
+
+/// A decoded PNG glyph from a CBDT or sbix strike.
+pub(crate) struct BitmapGlyph {
+    image: RgbaImage,
+    /// Offset of the image's left and bottom edges from the glyph origin, y
+    /// up, in strike pixels.
+    x: f32,
+    y: f32,
+    pixels_per_em: f32,
+}
+
+impl BitmapGlyph {
+    /// Glyph `id` from the strike of `face` best suited to `ppem`, if it has a
+    /// PNG one.
+    pub(crate) fn decode(face: &ttf_parser::Face, id: GlyphId, ppem: u16) -> Option<Self> {
+        let raster = face.glyph_raster_image(ttf_parser::GlyphId(id.0), ppem)?;
+        if raster.format != RasterImageFormat::PNG {
+            return None;
+        }
+        let image = decode(ImageSource::Encoded(raster.data)).ok()?.into_owned();
+        Some(Self {
+            image,
+            x: raster.x as f32,
+            y: raster.y as f32,
+            pixels_per_em: raster.pixels_per_em.max(1) as f32,
+        })
+    }
+}
+
+/// Where an emoji image placed at `origin` on the baseline is drawn: one em
+/// square, centered on the font's ascent–descent box.
+pub(crate) fn image_box(font: &FontHandle, size: f32, origin: Point) -> Rect {
+    let sf = font.arc().as_scaled(PxScale::from(size));
+    let em = font
+        .arc()
+        .units_per_em()
+        .map_or(size, |upem| upem * sf.v_scale_factor());
+    let middle = origin.y - (sf.ascent() + sf.descent()) / 2.0;
+    Rect::new(origin.x, middle - em / 2.0, em, em)
+}
+
+impl Canvas {
+    /// Draw glyph `id` of face `face` in color if the font has a bitmap
+    /// (CBDT, sbix) or COLR version of it. Returns `false`, drawing nothing,
+    /// for plain outline glyphs.
+    ///
+    /// `foreground` supplies the text color that COLR glyphs may reference.
+    pub(crate) fn draw_color_glyph(
+        &mut self,
+        font: &FontHandle,
+        face: usize,
+        id: GlyphId,
+        size: f32,
+        origin: Point,
+        foreground: &Paint,
+    ) -> bool {
+        let ab_face = font.face(face);
+        // Pixels per font unit.
+        let scale = ab_face
+            .as_scaled(font.face_scale(face, size))
+            .h_scale_factor();
+        let ppem = ab_face.units_per_em().unwrap_or(1000.0) * scale;
+
+        if let Some(bitmap) = font.bitmap_glyph(face, id, ppem.round() as u16) {
+            // Strike pixels, y up from the baseline to the image's bottom edge.
+            let s = ppem / bitmap.pixels_per_em;
+            let (w, h) = bitmap.image.dimensions();
+            let rect = Rect::new(
+                origin.x + bitmap.x * s,
+                origin.y - (bitmap.y + h as f32) * s,
+                w as f32 * s,
+                h as f32 * s,
+            );
+            let opts = ImageOptions::new().fit(ImageFit::Fill);
+            // Already decoded, so this can't fail.
+            let _ = self.draw_image(&bitmap.image, rect, &opts);
+            return true;
+        }
+
+        let Some(ttf) = font.tables(face) else {
+            return false;
+        };
+        let gid = ttf_parser::GlyphId(id.0);
+        if ttf.is_color_glyph(gid) {
+            let fg = match foreground {
+                Paint::Solid(c) => *c,
+                Paint::LinearGradient { gradient, .. }
+                | Paint::RadialGradient { gradient, .. }
+                | Paint::ConicGradient { gradient, .. } => {
+                    gradient.stops.first().map_or(Color::BLACK, |s| s.color)
+                }
+            };
+            let fg = ttf_parser::RgbaColor::new(fg.r, fg.g, fg.b, fg.a);
+
+            self.save();
+            self.translate(origin.x, origin.y);
+            self.scale(scale, -scale);
+            let mut painter = ColrPainter {
+                canvas: self,
+                face: ttf,
+                outline: Path::new(),
+                outline_is_clip: false,
+                transforms: Vec::new(),
+            };
+            ttf.paint_color_glyph(gid, 0, fg, &mut painter);
+            self.restore();
+            return true;
+        }
+        false
+    }
+}
+
+/// Replays a COLR glyph onto the canvas, in font units with y up.
+struct ColrPainter<'c, 'f> {
+    canvas: &'c mut Canvas,
+    face: &'c ttf_parser::Face<'f>,
+    /// The last outline requested.
+    outline: Path,
+    /// Whether `outline` has been pushed as a clip (COLRv1) rather than being
+    /// the shape to fill (COLRv0 layers).
+    outline_is_clip: bool,
+    transforms: Vec<Transform>,
+}
+
+impl<'f> colr::Painter<'f> for ColrPainter<'_, 'f> {
+    fn outline_glyph(&mut self, glyph_id: ttf_parser::GlyphId) {
+        let mut builder = OutlineBuilder(Path::new());
+        self.face.outline_glyph(glyph_id, &mut builder);
+        self.outline = builder.0;
+        self.outline_is_clip = false;
+    }
+
+    fn paint(&mut self, paint: colr::Paint<'f>) {
+        let paint = map_paint(paint);
+        if !self.outline_is_clip {
+            self.canvas
+                .fill_path(&self.outline, FillRule::NonZero, paint);
+            return;
+        }
+        // Fill everything the clips leave visible.
+        let full = Rect::new(
+            0.0,
+            0.0,
+            self.canvas.width() as f32,
+            self.canvas.height() as f32,
+        );
+        if let Some(inv) = self.canvas.transform().invert() {
+            let area = Path::new().rect(inv.map_rect(full));
+            self.canvas.fill_path(&area, FillRule::NonZero, paint);
+        }
+    }
+
+    fn push_clip(&mut self) {
+        self.canvas.push_clip_path(&self.outline, FillRule::NonZero);
+        self.outline_is_clip = true;
+    }
+
+    fn push_clip_box(&mut self, clipbox: colr::ClipBox) {
+        let rect = Rect::from_ltrb(clipbox.x_min, clipbox.y_min, clipbox.x_max, clipbox.y_max);
+        self.canvas
+            .push_clip_path(&Path::new().rect(rect), FillRule::NonZero);
+        self.outline_is_clip = true;
+    }
+
+    fn pop_clip(&mut self) {
+        self.canvas.pop_clip();
+    }
+
+    fn push_layer(&mut self, mode: colr::CompositeMode) {
+        use colr::CompositeMode as M;
+        let blend = match mode {
+            M::Clear => BlendMode::Clear,
+            M::Source => BlendMode::Source,
+            // Keep the backdrop: draw the layer, then throw it away.
+            M::Destination => return self.canvas.push_layer(0.0, BlendMode::SourceOver),
+            M::SourceOver => BlendMode::SourceOver,
+            M::DestinationOver => BlendMode::DestinationOver,
+            M::SourceIn => BlendMode::SourceIn,
+            M::DestinationIn => BlendMode::DestinationIn,
+            M::SourceOut => BlendMode::SourceOut,
+            M::DestinationOut => BlendMode::DestinationOut,
+            M::SourceAtop => BlendMode::SourceAtop,
+            M::DestinationAtop => BlendMode::DestinationAtop,
+            M::Xor => BlendMode::Xor,
+            M::Plus => BlendMode::Add,
+            M::Screen => BlendMode::Screen,
+            M::Overlay => BlendMode::Overlay,
+            M::Darken => BlendMode::Darken,
+            M::Lighten => BlendMode::Lighten,
+            M::ColorDodge => BlendMode::ColorDodge,
+            M::ColorBurn => BlendMode::ColorBurn,
+            M::HardLight => BlendMode::HardLight,
+            M::SoftLight => BlendMode::SoftLight,
+            M::Difference => BlendMode::Difference,
+            M::Exclusion => BlendMode::Exclusion,
+            M::Multiply => BlendMode::Multiply,
+            // Non-separable modes aren't supported; composite normally.
+            M::Hue | M::Saturation | M::Color | M::Luminosity => BlendMode::SourceOver,
+        };
+        self.canvas.push_layer(1.0, blend);
+    }
+
+    fn pop_layer(&mut self) {
+        self.canvas.pop_layer();
+    }
+
+    fn push_transform(&mut self, t: ttf_parser::Transform) {
+        self.transforms.push(self.canvas.transform());
+        self.canvas
+            .concat(Transform::new(t.a, t.b, t.c, t.d, t.e, t.f));
+    }
+
+    fn pop_transform(&mut self) {
+        if let Some(t) = self.transforms.pop() {
+            self.canvas.set_transform(t);
+        }
+    }
+}
+
+/// Convert a COLR paint, in font units, to a [`Paint`]. Two-circle radial
+/// gradients are approximated by a single circle around the end circle.
+fn map_paint(paint: colr::Paint<'_>) -> Paint {
+    let color = |c: ttf_parser::RgbaColor| Color::rgba(c.red, c.green, c.blue, c.alpha);
+    let extend = |e: colr::GradientExtend| match e {
+        colr::GradientExtend::Pad => ExtendMode::Pad,
+        colr::GradientExtend::Repeat => ExtendMode::Repeat,
+        colr::GradientExtend::Reflect => ExtendMode::Reflect,
+    };
+    let stops = |stops: colr::GradientStopsIter<'_, '_>, map: &dyn Fn(f32) -> f32| {
+        stops
+            .map(|s| (map(s.stop_offset), color(s.color)))
+            .collect::<Vec<_>>()
+    };
+
+    match paint {
+        colr::Paint::Solid(c) => Paint::Solid(color(c)),
+        colr::Paint::LinearGradient(g) => {
+            // Color lines run parallel to p0→p2, so the gradient axis is p0→p1
+            // projected onto the normal of p0→p2.
+            let (p0, p1) = (Point::new(g.x0, g.y0), Point::new(g.x1, g.y1));
+            let normal = (g.y0 - g.y2, g.x2 - g.x0);
+            let len_sq = normal.0 * normal.0 + normal.1 * normal.1;
+            let end = if len_sq > 0.0 {
+                let k = ((p1.x - p0.x) * normal.0 + (p1.y - p0.y) * normal.1) / len_sq;
+                Point::new(p0.x + normal.0 * k, p0.y + normal.1 * k)
+            } else {
+                p1
+            };
+            Paint::linear(p0, end, &stops(g.stops(0, &[]), &|t| t)).extend(extend(g.extend))
+        }
+        colr::Paint::RadialGradient(g) => {
+            let r1 = g.r1.max(f32::EPSILON);
+            let remap = |t: f32| (g.r0 + t * (g.r1 - g.r0)) / r1;
+            Paint::radial(Point::new(g.x1, g.y1), r1, &stops(g.stops(0, &[]), &remap))
+                .extend(extend(g.extend))
+        }
+        colr::Paint::SweepGradient(g) => {
+            // Angles are degrees counter-clockwise, which is the positive
+            // direction in y-up font units.
+            let remap = |t: f32| (g.start_angle + t * (g.end_angle - g.start_angle)) / 360.0;
+            Paint::conic(
+                Point::new(g.center_x, g.center_y),
+                0.0,
+                &stops(g.stops(0, &[]), &remap),
+            )
+            .extend(extend(g.extend))
+        }
+    }
+}
+
+/// Collects a `ttf_parser` outline into a [`Path`].
+struct OutlineBuilder(Path);
+
+impl OutlineBuilder {
+    fn map(&mut self, f: impl FnOnce(Path) -> Path) {
+        self.0 = f(std::mem::take(&mut self.0));
+    }
+}
+
+impl ttf_parser::OutlineBuilder for OutlineBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.map(|p| p.move_to(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.map(|p| p.line_to(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.map(|p| p.quad_to(x1, y1, x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.map(|p| p.cubic_to(x1, y1, x2, y2, x, y));
+    }
+
+    fn close(&mut self) {
+        self.map(Path::close);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_sequence(s: &str) -> Option<&str> {
+        sequence_len(s).map(|n| &s[..n])
+    }
+
+    #[test]
+    fn sequences_include_modifiers_and_joins() {
+        assert_eq!(first_sequence("👍🏽 ok"), Some("👍🏽"));
+        assert_eq!(first_sequence("👩‍💻!"), Some("👩‍💻"));
+        assert_eq!(first_sequence("❤️‍🔥"), Some("❤️‍🔥"));
+        assert_eq!(first_sequence("🇩🇪🇫🇷"), Some("🇩🇪"));
+        assert_eq!(first_sequence("1️⃣2"), Some("1️⃣"));
+        assert_eq!(first_sequence("🏴󠁧󠁢󠁳󠁣󠁴󠁿"), Some("🏴󠁧󠁢󠁳󠁣󠁴󠁿"));
+        // A trailing ZWJ with nothing to join stays out of the sequence.
+        assert_eq!(first_sequence("😀\u{200D}a"), Some("😀"));
+        assert_eq!(first_sequence("12"), None);
+        assert_eq!(first_sequence("☺\u{FE0E}"), None);
+        assert_eq!(first_sequence("abc"), None);
+    }
+
+    #[test]
+    fn directory_matches_names_with_and_without_vs16() {
+        let dir = std::env::temp_dir().join(format!("sd-emoji-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let red = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+        red.save(dir.join("1f44d-1f3fd.png")).unwrap();
+        red.save(dir.join("2764.png")).unwrap();
+
+        let emoji = EmojiDirectory::open(&dir).unwrap();
+        assert!(emoji.lookup("👍🏽").is_some());
+        assert!(emoji.lookup("❤️").is_some());
+        assert!(emoji.lookup("👍").is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn draw_at(font: &FontHandle, text: &str, x: f32, y: f32) -> RgbaImage {
+        let mut c = Canvas::new(64, 64);
+        let lines = [crate::TextLine {
+            text: text.into(),
+            width_px: crate::measure_line(font, 20.0, text),
+        }];
+        let opts = crate::TextOptions::new(font.clone(), 20.0);
+        c.draw_text_at(x, y, crate::Anchor::BaselineLeft, &lines, &opts)
+            .unwrap();
+        c.finish().buf
+    }
+
+    #[test]
+    fn directory_emoji_is_drawn_at_the_pen() {
+        let dir = std::env::temp_dir().join(format!("sd-emoji-draw-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let red = RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255]));
+        red.save(dir.join("1f600.png")).unwrap();
+        let emoji = EmojiDirectory::open(&dir).unwrap();
+        let font = crate::test_fonts::dejavu().emoji_images(&emoji);
+        let img = draw_at(&font, "a😀", 10.0, 40.0);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let pen = crate::measure_line(&font, 20.0, "a");
+        let b = image_box(&font, 20.0, Point::new(10.0 + pen, 40.0));
+        let (cx, cy) = (b.x + b.width / 2.0, b.y + b.height / 2.0);
+        assert_eq!(img.get_pixel(cx as u32, cy as u32).0, [255, 0, 0, 255]);
+        // Nothing red left of the pen, where the `a` is.
+        let red_at = |x, y| img.get_pixel(x, y).0 == [255, 0, 0, 255];
+        assert!((0..(10.0 + pen) as u32).all(|x| (0..64).all(|y| !red_at(x, y))));
+    }
+
+    #[test]
+    fn bitmap_glyphs_are_decoded_once() {
+        use crate::test_fonts::{TINY_EMOJI_COLOR, tiny};
+        let font = tiny();
+        // At 20 px the one-em bitmap covers (10, 20)–(30, 40).
+        let img = draw_at(&font, "😀", 10.0, 40.0);
+        assert_eq!(img.get_pixel(20, 30).0, TINY_EMOJI_COLOR);
+
+        // Swap the cached decode for a green one: the next draw must use it
+        // rather than decoding the font's blue PNG again.
+        {
+            let mut cache = font.bitmap_cache(0);
+            assert_eq!(cache.len(), 1);
+            let glyph = cache.values_mut().next().unwrap();
+            let cached = glyph.as_ref().unwrap();
+            *glyph = Some(Arc::new(BitmapGlyph {
+                image: RgbaImage::from_pixel(16, 16, image::Rgba([0, 255, 0, 255])),
+                ..**cached
+            }));
+        }
+        let img = draw_at(&font, "😀", 10.0, 40.0);
+        assert_eq!(img.get_pixel(20, 30).0, [0, 255, 0, 255]);
+        assert_eq!(font.bitmap_cache(0).len(), 1);
+    }
+
+    #[test]
+    fn colr_glyphs_are_painted_from_the_palette() {
+        let font = crate::test_fonts::colr();
+        let img = draw_at(&font, "\u{F0500}", 10.0, 40.0);
+        // Red from the font's palette, not the white text color.
+        assert_eq!(img.get_pixel(20, 30).0, [255, 0, 0, 255]);
+        assert!(img.pixels().all(|p| p[3] == 0 || p.0 != [255; 4]));
+    }
+}
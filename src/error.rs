@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum RenderError {
     #[error("font not found: '{0}'")]
     FontNotFound(String),
@@ -11,9 +12,36 @@ pub enum RenderError {
         source: std::io::Error,
     },
 
+    #[error("failed to read emoji directory '{path}': {source}")]
+    EmojiDirIo {
+        path: String,
+        source: std::io::Error,
+    },
+
+    #[error("invalid markup at byte {offset}: {message}")]
+    Markup { offset: usize, message: String },
+
     #[error("failed to parse font data: {0}")]
     FontParse(#[from] ab_glyph::InvalidFont),
 
     #[error("PNG encoding failed: {0}")]
     PngEncode(#[from] image::ImageError),
+
+    #[error("image decoding failed: {0}")]
+    ImageDecode(#[source] image::ImageError),
+
+    #[error("invalid base64 image data: {0}")]
+    Base64Decode(#[from] base64::DecodeError),
+
+    #[cfg(feature = "svg")]
+    #[error("failed to parse SVG: {0}")]
+    SvgParse(#[from] resvg::usvg::Error),
+
+    #[cfg(feature = "qr")]
+    #[error("QR encoding failed: {0}")]
+    QrEncode(#[from] qrcode::types::QrError),
+
+    #[cfg(feature = "qr")]
+    #[error("QR code needs {modules} modules but only {side} px are available")]
+    QrTooSmall { modules: u32, side: u32 },
 }
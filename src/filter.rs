@@ -0,0 +1,381 @@
+use image::RgbaImage;
+
+use crate::{
+    color::Color,
+    pipeline::{srgb_decode, srgb_encode},
+};
+
+/// A per-pixel or neighbourhood image filter, modelled on CSS `filter` functions.
+///
+/// Apply to the whole canvas or a region with [`crate::Canvas::apply_filter`] and
+/// [`crate::Canvas::apply_filter_in`], or to a group of draws with
+/// [`crate::Canvas::with_filters`]. Amounts of `1.0` are the full effect and `0.0`
+/// leaves pixels unchanged, except where noted.
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::Filter;
+///
+/// // "Action unavailable": desaturated and dimmed.
+/// canvas.apply_filter(&Filter::Grayscale(1.0));
+/// canvas.apply_filter(&Filter::Brightness(0.6));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    /// Gaussian blur with the given standard deviation in pixels, like CSS `blur()`.
+    Blur(f32),
+    /// Box blur with the given radius in pixels: cheaper, with a flatter falloff.
+    BoxBlur(u32),
+    /// Desaturate towards luminance.
+    Grayscale(f32),
+    /// Multiply color channels: `0.5` halves, `1.0` is unchanged, `1.5` brightens.
+    Brightness(f32),
+    /// Scale distance from mid-grey: `0.0` is flat grey, `1.0` is unchanged.
+    Contrast(f32),
+    /// Rotate hues by the given angle in radians, keeping luminance.
+    HueRotate(f32),
+    /// Recolor towards `color` scaled by each pixel's luminance, so shading is
+    /// kept. The color's alpha sets the strength.
+    Tint(Color),
+    /// Invert color channels.
+    Invert(f32),
+    /// Multiply alpha: `0.0` is fully transparent, `1.0` is unchanged.
+    Opacity(f32),
+}
+
+impl Filter {
+    /// Apply the filter to the pixels of `img` inside `(x0, y0)..(x1, y1)`.
+    ///
+    /// Blurs sample only inside the region, clamping at its edges.
+    pub(crate) fn apply(&self, img: &mut RgbaImage, x0: u32, y0: u32, x1: u32, y1: u32) {
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+        match *self {
+            Filter::Blur(_) | Filter::BoxBlur(_) => {
+                blur_region(img, (x0, y0, x1, y1), |plane, w, h| {
+                    self.blur_plane(plane, w, h)
+                })
+            }
+            _ => {
+                for y in y0..y1 {
+                    for x in x0..x1 {
+                        let p = img.get_pixel_mut(x, y);
+                        let unit = |v: u8| v as f32 / 255.0;
+                        let rgb = [unit(p[0]), unit(p[1]), unit(p[2])];
+                        let (rgb, a) = self.map_color(rgb, unit(p[3]));
+                        let q = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+                        p.0 = [q(rgb[0]), q(rgb[1]), q(rgb[2]), q(a)];
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply the filter to premultiplied float pixels, `width` to a row, inside
+    /// `(x0, y0)..(x1, y1)`, without rounding them to bytes.
+    ///
+    /// With `linear` the channels are linear light: blurs average them as they
+    /// are, and the per-pixel filters see them sRGB-encoded as on straight
+    /// surfaces.
+    pub(crate) fn apply_premul(
+        &self,
+        data: &mut [[f32; 4]],
+        width: u32,
+        (x0, y0, x1, y1): (u32, u32, u32, u32),
+        linear: bool,
+    ) {
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+        let (w, h) = ((x1 - x0) as usize, (y1 - y0) as usize);
+        let index = |x: usize, y: usize| (y0 as usize + y) * width as usize + x0 as usize + x;
+        if let Filter::Blur(_) | Filter::BoxBlur(_) = self {
+            let mut planes: [Vec<f32>; 4] =
+                std::array::from_fn(|c| (0..w * h).map(|i| data[index(i % w, i / w)][c]).collect());
+            for plane in &mut planes {
+                self.blur_plane(plane, w, h);
+            }
+            for i in 0..w * h {
+                data[index(i % w, i / w)] = planes.each_ref().map(|p| p[i]);
+            }
+            return;
+        }
+        let to_srgb = |v: f32| if linear { srgb_encode(v) } else { v };
+        let from_srgb = |v: f32| if linear { srgb_decode(v) } else { v };
+        for y in 0..h {
+            for x in 0..w {
+                let p = &mut data[index(x, y)];
+                let a = p[3].clamp(0.0, 1.0);
+                // Nothing to recolor, and no filter adds coverage.
+                if a <= 0.0 {
+                    continue;
+                }
+                let straight = |v: f32| to_srgb((v / a).clamp(0.0, 1.0));
+                let rgb = [straight(p[0]), straight(p[1]), straight(p[2])];
+                let (rgb, a) = self.map_color(rgb, a);
+                let (rgb, a) = (rgb.map(|v| v.clamp(0.0, 1.0)), a.clamp(0.0, 1.0));
+                *p = [
+                    from_srgb(rgb[0]) * a,
+                    from_srgb(rgb[1]) * a,
+                    from_srgb(rgb[2]) * a,
+                    a,
+                ];
+            }
+        }
+    }
+
+    /// Run a blur filter over one premultiplied channel of `w × h` cells.
+    fn blur_plane(&self, plane: &mut [f32], w: usize, h: usize) {
+        match *self {
+            Filter::Blur(sigma) => gaussian_blur(plane, w, h, sigma, None),
+            Filter::BoxBlur(r) => box_blur(plane, w, h, r as usize, None),
+            _ => {}
+        }
+    }
+
+    /// Straight-alpha color transform for the per-pixel filters.
+    fn map_color(&self, c: [f32; 3], a: f32) -> ([f32; 3], f32) {
+        let lum = 0.2126 * c[0] + 0.7152 * c[1] + 0.0722 * c[2];
+        let mix = |from: [f32; 3], to: [f32; 3], t: f32| {
+            [0, 1, 2].map(|i| from[i] + (to[i] - from[i]) * t.clamp(0.0, 1.0))
+        };
+        match *self {
+            Filter::Grayscale(amount) => (mix(c, [lum; 3], amount), a),
+            Filter::Brightness(b) => (c.map(|v| v * b.max(0.0)), a),
+            Filter::Contrast(k) => (c.map(|v| (v - 0.5) * k.max(0.0) + 0.5), a),
+            Filter::HueRotate(angle) => {
+                // The CSS/SVG `hueRotate` matrix.
+                let (sin, cos) = angle.sin_cos();
+                let m = [
+                    [
+                        0.213 + cos * 0.787 - sin * 0.213,
+                        0.715 - cos * 0.715 - sin * 0.715,
+                        0.072 - cos * 0.072 + sin * 0.928,
+                    ],
+                    [
+                        0.213 - cos * 0.213 + sin * 0.143,
+                        0.715 + cos * 0.285 + sin * 0.140,
+                        0.072 - cos * 0.072 - sin * 0.283,
+                    ],
+                    [
+                        0.213 - cos * 0.213 - sin * 0.787,
+                        0.715 - cos * 0.715 + sin * 0.715,
+                        0.072 + cos * 0.928 + sin * 0.072,
+                    ],
+                ];
+                (
+                    m.map(|row| row[0] * c[0] + row[1] * c[1] + row[2] * c[2]),
+                    a,
+                )
+            }
+            Filter::Tint(color) => {
+                let target = [color.r, color.g, color.b].map(|v| v as f32 / 255.0 * lum);
+                (mix(c, target, color.a as f32 / 255.0), a)
+            }
+            Filter::Invert(amount) => (mix(c, c.map(|v| 1.0 - v), amount), a),
+            Filter::Opacity(o) => (c, a * o.clamp(0.0, 1.0)),
+            Filter::Blur(_) | Filter::BoxBlur(_) => (c, a),
+        }
+    }
+}
+
+/// Run `blur` over each premultiplied channel of a region of `img`.
+fn blur_region(
+    img: &mut RgbaImage,
+    (x0, y0, x1, y1): (u32, u32, u32, u32),
+    blur: impl Fn(&mut [f32], usize, usize),
+) {
+    let (w, h) = ((x1 - x0) as usize, (y1 - y0) as usize);
+    let mut planes = vec![vec![0.0_f32; w * h]; 4];
+    for y in 0..h {
+        for x in 0..w {
+            let p = img.get_pixel(x0 + x as u32, y0 + y as u32);
+            let a = p[3] as f32 / 255.0;
+            for (c, plane) in planes.iter_mut().enumerate() {
+                plane[y * w + x] = if c == 3 { a } else { p[c] as f32 / 255.0 * a };
+            }
+        }
+    }
+    for plane in &mut planes {
+        blur(plane, w, h);
+    }
+    for y in 0..h {
+        for x in 0..w {
+            let i = y * w + x;
+            let a = planes[3][i];
+            let q = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+            let straight = |c: usize| if a > 0.0 { planes[c][i] / a } else { 0.0 };
+            img.put_pixel(
+                x0 + x as u32,
+                y0 + y as u32,
+                image::Rgba([q(straight(0)), q(straight(1)), q(straight(2)), q(a)]),
+            );
+        }
+    }
+}
+
+/// Approximate a Gaussian blur with standard deviation `sigma` by three box blurs.
+///
+/// Cells beyond the edge read as `outside`, or repeat the edge cell when `None`.
+pub(crate) fn gaussian_blur(
+    data: &mut [f32],
+    w: usize,
+    h: usize,
+    sigma: f32,
+    outside: Option<f32>,
+) {
+    // NaN or infinite sigmas would overflow the box sizes.
+    if !sigma.is_finite() || sigma <= 0.0 || w == 0 || h == 0 {
+        return;
+    }
+    // Past the size of the data a wider blur only costs time.
+    let sigma = sigma.min(w.max(h) as f32);
+    for size in box_sizes(sigma, 3) {
+        let r = (size - 1) / 2;
+        box_blur(data, w, h, r, outside);
+    }
+}
+
+/// Box widths whose successive application approximates a Gaussian of `sigma`.
+fn box_sizes(sigma: f32, n: usize) -> Vec<usize> {
+    let n_f = n as f32;
+    let ideal = (12.0 * sigma * sigma / n_f + 1.0).sqrt();
+    let mut wl = ideal.floor() as usize;
+    if wl.is_multiple_of(2) {
+        wl -= 1;
+    }
+    let wu = wl + 2;
+    let wl_f = wl as f32;
+    let m = ((12.0 * sigma * sigma - n_f * wl_f * wl_f - 4.0 * n_f * wl_f - 3.0 * n_f)
+        / (-4.0 * wl_f - 4.0))
+        .round()
+        .max(0.0) as usize;
+    (0..n).map(|i| if i < m { wl } else { wu }).collect()
+}
+
+/// Separable box blur of radius `r`, horizontal then vertical. Edges as in
+/// [`gaussian_blur`].
+pub(crate) fn box_blur(data: &mut [f32], w: usize, h: usize, r: usize, outside: Option<f32>) {
+    if r == 0 {
+        return;
+    }
+    let r = r.min(w.max(h));
+    let mut line = Vec::new();
+    for y in 0..h {
+        line.clear();
+        line.extend((0..w).map(|x| data[y * w + x]));
+        blur_line(&line, r, outside, |x, v| data[y * w + x] = v);
+    }
+    for x in 0..w {
+        line.clear();
+        line.extend((0..h).map(|y| data[y * w + x]));
+        blur_line(&line, r, outside, |y, v| data[y * w + x] = v);
+    }
+}
+
+/// Running-sum box filter over one row or column.
+fn blur_line(line: &[f32], r: usize, outside: Option<f32>, mut put: impl FnMut(usize, f32)) {
+    let n = line.len() as isize;
+    let r = r as isize;
+    let at = |i: isize| match outside {
+        Some(v) if i < 0 || i >= n => v,
+        _ => line[i.clamp(0, n - 1) as usize],
+    };
+    let scale = 1.0 / (2 * r + 1) as f32;
+    let mut sum: f32 = (-r..=r).map(at).sum();
+    for i in 0..n {
+        put(i as usize, sum * scale);
+        sum += at(i + r + 1) - at(i - r);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_to(c: Color, f: Filter) -> [u8; 4] {
+        let mut img = RgbaImage::from_pixel(1, 1, image::Rgba([c.r, c.g, c.b, c.a]));
+        f.apply(&mut img, 0, 0, 1, 1);
+        img.get_pixel(0, 0).0
+    }
+
+    #[test]
+    fn blur_preserves_mass_away_from_edges() {
+        let mut v = vec![0.0; 40 * 40];
+        for y in 15..25 {
+            for x in 15..25 {
+                v[y * 40 + x] = 1.0;
+            }
+        }
+        gaussian_blur(&mut v, 40, 40, 3.0, Some(0.0));
+        let total: f32 = v.iter().sum();
+        assert!((total - 100.0).abs() < 0.5, "{total}");
+        assert!(v[15 * 40 + 14] > 0.0, "blur spreads past the edge");
+    }
+
+    #[test]
+    fn clamped_blur_keeps_flat_color() {
+        let mut img = RgbaImage::from_pixel(8, 8, image::Rgba([40, 80, 120, 255]));
+        Filter::Blur(2.0).apply(&mut img, 2, 2, 6, 6);
+        assert_eq!(img.get_pixel(2, 2).0, [40, 80, 120, 255]);
+    }
+
+    #[test]
+    fn extreme_blur_amounts_finish() {
+        let px = image::Rgba([40, 80, 120, 255]);
+        for f in [
+            Filter::Blur(f32::NAN),
+            Filter::Blur(f32::INFINITY),
+            Filter::Blur(1e30),
+            Filter::BoxBlur(u32::MAX),
+        ] {
+            let mut img = RgbaImage::from_pixel(8, 8, px);
+            f.apply(&mut img, 0, 0, 8, 8);
+            assert_eq!(img.get_pixel(3, 3).0, px.0, "{f:?}");
+        }
+        let mut v = vec![1.0; 16];
+        gaussian_blur(&mut v, 4, 4, f32::INFINITY, Some(0.0));
+        box_blur(&mut v, 4, 4, usize::MAX, Some(0.0));
+        assert!(v.iter().all(|x| x.is_finite()));
+    }
+
+    #[test]
+    fn grayscale_and_invert() {
+        let [r, g, b, _] = apply_to(Color::rgb(255, 0, 0), Filter::Grayscale(1.0));
+        assert!(r == g && g == b);
+        assert_eq!(
+            apply_to(Color::rgb(255, 0, 100), Filter::Invert(1.0)),
+            [0, 255, 155, 255]
+        );
+    }
+
+    #[test]
+    fn hue_rotate_full_turn_is_identity() {
+        let [r, g, b, _] = apply_to(
+            Color::rgb(200, 100, 50),
+            Filter::HueRotate(std::f32::consts::TAU),
+        );
+        assert!(r.abs_diff(200) <= 1 && g.abs_diff(100) <= 1 && b.abs_diff(50) <= 1);
+    }
+
+    #[test]
+    fn tint_follows_luminance() {
+        assert_eq!(
+            apply_to(Color::WHITE, Filter::Tint(Color::rgb(255, 0, 0))),
+            [255, 0, 0, 255]
+        );
+        assert_eq!(
+            apply_to(Color::BLACK, Filter::Tint(Color::rgb(255, 0, 0))),
+            [0, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn opacity_scales_alpha_only() {
+        assert_eq!(
+            apply_to(Color::rgb(10, 20, 30), Filter::Opacity(0.5)),
+            [10, 20, 30, 128]
+        );
+    }
+}
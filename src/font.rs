@@ -1,21 +1,351 @@
-use std::{collections::HashMap, path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
 
-use ab_glyph::FontArc;
+use ab_glyph::{Font, FontArc, GlyphId, OutlineCurve, PxScale, ScaleFont};
 
-use crate::error::RenderError;
+use crate::{
+    emoji::{BitmapGlyph, EmojiDirectory},
+    error::RenderError,
+    geom::Point,
+};
+
+/// An OpenType feature setting applied when text is shaped, such as turning
+/// ligatures off or selecting tabular figures.
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::FontFeature;
+///
+/// let digits = font.feature(FontFeature::on(b"tnum")); // counters that don't jiggle
+/// let plain = font.feature(FontFeature::off(b"liga"));
+/// let alt = font.feature(FontFeature::parse("ss01").unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontFeature {
+    /// Four-byte feature tag, e.g. `*b"liga"`.
+    pub tag: [u8; 4],
+    /// `0` turns the feature off, `1` on; larger values pick an alternate.
+    pub value: u32,
+}
+
+impl FontFeature {
+    pub const fn new(tag: &[u8; 4], value: u32) -> Self {
+        Self { tag: *tag, value }
+    }
+
+    pub const fn on(tag: &[u8; 4]) -> Self {
+        Self::new(tag, 1)
+    }
+
+    pub const fn off(tag: &[u8; 4]) -> Self {
+        Self::new(tag, 0)
+    }
+
+    /// Parse `tag`, `+tag` (on), `-tag` (off) or `tag=value`.
+    /// Returns `None` if the tag isn't four printable ASCII characters.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name.trim(), value.trim().parse().ok()?),
+            None => match s.strip_prefix('-') {
+                Some(name) => (name, 0),
+                None => (s.strip_prefix('+').unwrap_or(s), 1),
+            },
+        };
+        let tag: [u8; 4] = name.as_bytes().try_into().ok()?;
+        tag.iter()
+            .all(u8::is_ascii_graphic)
+            .then_some(Self { tag, value })
+    }
+}
+
+/// Decoded CBDT/sbix glyphs by glyph id and requested pixels per em.
+type BitmapCache = HashMap<(u16, u16), Option<Arc<BitmapGlyph>>>;
+
+/// Shaper tables parsed from a font's bytes, or `None` if it can't read them.
+type ParsedTables<'a> = Option<rustybuzz::Face<'a>>;
+
+self_cell::self_cell!(
+    /// A font with its OpenType tables parsed once, borrowing its bytes.
+    struct FaceTables {
+        owner: FontArc,
+        #[covariant]
+        dependent: ParsedTables,
+    }
+);
+
+/// A font with its OpenType tables parsed once, for shaping and the metrics
+/// and glyph data ab_glyph doesn't expose.
+struct LoadedFace {
+    tables: FaceTables,
+    /// Whether the face has color glyph tables (COLR, CBDT or sbix).
+    color: bool,
+    bitmaps: Mutex<BitmapCache>,
+}
+
+impl LoadedFace {
+    fn new(font: FontArc) -> Self {
+        let tables = FaceTables::new(font, |font| {
+            rustybuzz::Face::from_slice(font.font_data(), 0)
+        });
+        let color = tables.borrow_dependent().as_ref().is_some_and(|face| {
+            let t = face.tables();
+            t.colr.is_some() || t.cbdt.is_some() || t.sbix.is_some()
+        });
+        Self {
+            tables,
+            color,
+            bitmaps: Mutex::default(),
+        }
+    }
+
+    fn font(&self) -> &FontArc {
+        self.tables.borrow_owner()
+    }
+}
+
+impl std::fmt::Debug for LoadedFace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LoadedFace")
+            .field("font", self.font())
+            .field("color", &self.color)
+            .finish_non_exhaustive()
+    }
+}
 
 /// A cheap-to-clone handle to a loaded font.
 ///
 /// Internally backed by an `Arc`, so cloning is O(1) and the font data is shared.
+/// A handle also carries the [`FontFeature`]s its text is shaped with, its
+/// fallback fonts and any emoji images, so measuring and drawing always agree.
 #[derive(Debug, Clone)]
-pub struct FontHandle(pub(crate) Arc<FontArc>);
+pub struct FontHandle {
+    font: Arc<LoadedFace>,
+    /// Fonts tried in order for characters `font` has no glyph for.
+    fallbacks: Arc<[Arc<LoadedFace>]>,
+    features: Arc<[FontFeature]>,
+    emoji: Option<EmojiDirectory>,
+}
 
 impl FontHandle {
+    fn new(font: FontArc) -> Self {
+        Self {
+            font: Arc::new(LoadedFace::new(font)),
+            fallbacks: Arc::new([]),
+            features: Arc::new([]),
+            emoji: None,
+        }
+    }
+
     pub(crate) fn arc(&self) -> &FontArc {
-        &self.0
+        self.font.font()
+    }
+
+    /// The same font, falling back to `font` (and then its own fallbacks) for
+    /// characters not covered by this handle's fonts. Call repeatedly to build
+    /// a chain, e.g. brand font → Noto Sans → Noto CJK → a symbol font.
+    ///
+    /// Fallback glyphs are drawn at the primary font's em size and shaped with
+    /// this handle's features.
+    pub fn fallback(mut self, font: &FontHandle) -> Self {
+        let mut fallbacks = self.fallbacks.to_vec();
+        fallbacks.push(font.font.clone());
+        fallbacks.extend(font.fallbacks.iter().cloned());
+        self.fallbacks = fallbacks.into();
+        if self.emoji.is_none() {
+            self.emoji = font.emoji.clone();
+        }
+        self
+    }
+
+    /// The same font, drawing emoji from `images` where it has a picture for
+    /// them. Other emoji still come from color fonts in the fallback chain.
+    pub fn emoji_images(mut self, images: &EmojiDirectory) -> Self {
+        self.emoji = Some(images.clone());
+        self
+    }
+
+    pub(crate) fn emoji(&self) -> Option<&EmojiDirectory> {
+        self.emoji.as_ref()
+    }
+
+    /// Face `i` of the chain: the primary font, then the fallbacks in order.
+    pub(crate) fn face(&self, i: usize) -> &FontArc {
+        self.loaded(i).font()
+    }
+
+    /// The parsed tables of face `i`, or `None` if the shaper can't read it.
+    pub(crate) fn tables(&self, i: usize) -> Option<&rustybuzz::Face<'_>> {
+        self.loaded(i).tables.borrow_dependent().as_ref()
+    }
+
+    fn loaded(&self, i: usize) -> &LoadedFace {
+        match i {
+            0 => &self.font,
+            i => &self.fallbacks[i - 1],
+        }
+    }
+
+    /// Whether face `i` has color (COLR, CBDT or sbix) glyphs.
+    pub(crate) fn is_color_face(&self, i: usize) -> bool {
+        self.loaded(i).color
+    }
+
+    /// Bitmap glyph `id` of face `i` from the strike best suited to `ppem`,
+    /// decoded on first use and cached with the face.
+    pub(crate) fn bitmap_glyph(
+        &self,
+        i: usize,
+        id: GlyphId,
+        ppem: u16,
+    ) -> Option<Arc<BitmapGlyph>> {
+        let mut cache = self
+            .loaded(i)
+            .bitmaps
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        cache
+            .entry((id.0, ppem))
+            .or_insert_with(|| {
+                let tables = self.tables(i)?;
+                BitmapGlyph::decode(tables, id, ppem).map(Arc::new)
+            })
+            .clone()
+    }
+
+    /// The decoded bitmap glyphs cached for face `i`.
+    #[cfg(test)]
+    pub(crate) fn bitmap_cache(&self, i: usize) -> std::sync::MutexGuard<'_, BitmapCache> {
+        self.loaded(i).bitmaps.lock().unwrap()
+    }
+
+    /// Index of the first face with a glyph for `ch`, or the primary font's
+    /// `.notdef` if none has one.
+    pub(crate) fn face_for(&self, ch: char) -> usize {
+        std::iter::once(&self.font)
+            .chain(self.fallbacks.iter())
+            .position(|f| f.font().glyph_id(ch).0 != 0)
+            .unwrap_or(0)
+    }
+
+    /// Like [`FontHandle::face_for`], but preferring a face with color
+    /// glyphs, for emoji that also have a monochrome version in text fonts.
+    pub(crate) fn emoji_face_for(&self, ch: char) -> usize {
+        std::iter::once(&self.font)
+            .chain(self.fallbacks.iter())
+            .position(|f| f.color && f.font().glyph_id(ch).0 != 0)
+            .unwrap_or_else(|| self.face_for(ch))
+    }
+
+    /// Scale for face `i` at font size `size`. Fallbacks are matched to the
+    /// primary font's pixels per em, since `PxScale` measures the line height
+    /// and fonts divide that up differently.
+    pub(crate) fn face_scale(&self, i: usize, size: f32) -> PxScale {
+        if i == 0 {
+            return PxScale::from(size);
+        }
+        let em = |f: &FontArc| f.units_per_em().unwrap_or(1000.0) / f.height_unscaled();
+        PxScale::from(size * em(self.arc()) / em(self.face(i)))
+    }
+
+    /// Position and thickness in pixels of the font's underline, or of its
+    /// strikeout line with `strikeout`, at `size`. The position is the line's
+    /// center relative to the baseline, y down.
+    pub(crate) fn decoration(&self, size: f32, strikeout: bool) -> (f32, f32) {
+        let sf = self.arc().as_scaled(PxScale::from(size));
+        let em = self.arc().units_per_em().unwrap_or(1000.0);
+        let metrics = self.tables(0).and_then(|face| {
+            if strikeout {
+                face.strikeout_metrics()
+            } else {
+                face.underline_metrics()
+            }
+        });
+        let (position, thickness) = match metrics {
+            Some(m) => (m.position as f32, m.thickness as f32),
+            // Fonts without the metrics: a line a twentieth of an em thick,
+            // just under the baseline or through the middle of lowercase.
+            None if strikeout => (em * 0.25, em * 0.05),
+            None => (-em * 0.1, em * 0.05),
+        };
+        // Positions give the line's top edge, y up.
+        let scale = sf.v_scale_factor();
+        let center = position - thickness / 2.0;
+        (-center * scale, (thickness * scale).max(1.0))
+    }
+
+    /// The same font with `feature` applied on top of this handle's features,
+    /// replacing any earlier setting of the same tag.
+    pub fn feature(mut self, feature: FontFeature) -> Self {
+        let mut features = self.features.to_vec();
+        features.retain(|f| f.tag != feature.tag);
+        features.push(feature);
+        self.features = features.into();
+        self
+    }
+
+    /// OpenType features used when shaping text in this font.
+    pub fn features(&self) -> &[FontFeature] {
+        &self.features
+    }
+
+    /// Outline of glyph `id` of face `face` at `size` as a vector path, with
+    /// the glyph origin (on the baseline) placed at `origin`. `None` for glyphs
+    /// without contours.
+    pub(crate) fn glyph_path(
+        &self,
+        face: usize,
+        id: GlyphId,
+        size: f32,
+        origin: Point,
+    ) -> Option<crate::path::Path> {
+        let font = self.face(face);
+        let outline = font.outline(id)?;
+        let sf = font.as_scaled(self.face_scale(face, size));
+        let (hs, vs) = (sf.h_scale_factor(), sf.v_scale_factor());
+        // Font units are y-up; canvas pixels are y-down.
+        let map = |p: ab_glyph::Point| Point::new(origin.x + p.x * hs, origin.y - p.y * vs);
+
+        Some(outline_path(&outline.curves, map))
     }
 }
 
+/// Convert outline curves to a path, closing each contour so strokes join at
+/// the contour start instead of leaving a notch.
+fn outline_path(
+    curves: &[OutlineCurve],
+    map: impl Fn(ab_glyph::Point) -> Point,
+) -> crate::path::Path {
+    let mut path = crate::path::Path::new();
+    for curve in curves {
+        let (from, to) = match *curve {
+            OutlineCurve::Line(a, b) => (a, b),
+            OutlineCurve::Quad(a, _, b) => (a, b),
+            OutlineCurve::Cubic(a, _, _, b) => (a, b),
+        };
+        let from = map(from);
+        if path.current_point() != Some(from) {
+            path = path.close().move_to(from.x, from.y);
+        }
+        let to = map(to);
+        path = match *curve {
+            OutlineCurve::Line(..) => path.line_to(to.x, to.y),
+            OutlineCurve::Quad(_, c, _) => {
+                let c = map(c);
+                path.quad_to(c.x, c.y, to.x, to.y)
+            }
+            OutlineCurve::Cubic(_, c1, c2, _) => {
+                let (c1, c2) = (map(c1), map(c2));
+                path.cubic_to(c1.x, c1.y, c2.x, c2.y, to.x, to.y)
+            }
+        };
+    }
+    path.close()
+}
+
 /// Stores named fonts. Load fonts once at startup, then retrieve handles by name.
 ///
 /// # Example
@@ -42,7 +372,7 @@ impl FontRegistry {
         bytes: &'static [u8],
     ) -> Result<FontHandle, RenderError> {
         let font = FontArc::try_from_slice(bytes)?;
-        let handle = FontHandle(Arc::new(font));
+        let handle = FontHandle::new(font);
         self.fonts.insert(name.into(), handle.clone());
         Ok(handle)
     }
@@ -54,7 +384,7 @@ impl FontRegistry {
         bytes: Vec<u8>,
     ) -> Result<FontHandle, RenderError> {
         let font = FontArc::try_from_vec(bytes)?;
-        let handle = FontHandle(Arc::new(font));
+        let handle = FontHandle::new(font);
         self.fonts.insert(name.into(), handle.clone());
         Ok(handle)
     }
@@ -74,6 +404,20 @@ impl FontRegistry {
         self.load_vec(name, bytes)
     }
 
+    /// The font registered as `primary` with the fonts named in `fallbacks`
+    /// tried in order for characters it doesn't cover.
+    ///
+    /// ```rust,ignore
+    /// let font = fonts.chain("brand", &["noto", "noto-cjk", "symbols"])?;
+    /// ```
+    pub fn chain(&self, primary: &str, fallbacks: &[&str]) -> Result<FontHandle, RenderError> {
+        fallbacks
+            .iter()
+            .try_fold(self.require(primary)?, |font, name| {
+                Ok(font.fallback(&self.require(name)?))
+            })
+    }
+
     /// Retrieve a previously registered font by name.
     pub fn get(&self, name: &str) -> Option<FontHandle> {
         self.fonts.get(name).cloned()
@@ -85,3 +429,46 @@ impl FontRegistry {
             .ok_or_else(|| RenderError::FontNotFound(name.to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feature_parse_accepts_css_like_syntax() {
+        assert_eq!(FontFeature::parse("liga"), Some(FontFeature::on(b"liga")));
+        assert_eq!(FontFeature::parse("+tnum"), Some(FontFeature::on(b"tnum")));
+        assert_eq!(FontFeature::parse("-kern"), Some(FontFeature::off(b"kern")));
+        assert_eq!(
+            FontFeature::parse("salt = 3"),
+            Some(FontFeature::new(b"salt", 3))
+        );
+        assert_eq!(FontFeature::parse("lig"), None);
+        assert_eq!(FontFeature::parse("ss01=x"), None);
+    }
+
+    #[test]
+    fn handles_are_shareable_across_threads() {
+        fn check<T: Send + Sync>() {}
+        check::<FontHandle>();
+    }
+
+    #[test]
+    fn outline_path_closes_every_contour() {
+        use crate::path::PathCmd;
+        let p = |x, y| ab_glyph::point(x, y);
+        let square = |x: f32| {
+            [
+                OutlineCurve::Line(p(x, 0.0), p(x + 1.0, 0.0)),
+                OutlineCurve::Line(p(x + 1.0, 0.0), p(x + 1.0, 1.0)),
+                OutlineCurve::Line(p(x + 1.0, 1.0), p(x, 0.0)),
+            ]
+        };
+        let curves: Vec<_> = square(0.0).into_iter().chain(square(5.0)).collect();
+        let path = outline_path(&curves, |q| Point::new(q.x, q.y));
+        let count = |f: fn(&PathCmd) -> bool| path.cmds.iter().filter(|c| f(c)).count();
+        assert_eq!(count(|c| matches!(c, PathCmd::MoveTo(_))), 2);
+        assert_eq!(count(|c| matches!(c, PathCmd::Close)), 2);
+        assert!(matches!(path.cmds.last(), Some(PathCmd::Close)));
+    }
+}
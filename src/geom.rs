@@ -0,0 +1,220 @@
+/// A point in canvas pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<(f32, f32)> for Point {
+    fn from((x, y): (f32, f32)) -> Self {
+        Self { x, y }
+    }
+}
+
+/// An axis-aligned rectangle in canvas pixel coordinates.
+///
+/// `(x, y)` is the top-left corner; the rectangle covers `[x, x + width]` ×
+/// `[y, y + height]`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Build a rectangle from its top-left and bottom-right corners.
+    pub fn from_ltrb(left: f32, top: f32, right: f32, bottom: f32) -> Self {
+        Self::new(left, top, right - left, bottom - top)
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    pub fn center(&self) -> Point {
+        Point::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    /// Shrink the rectangle by `d` pixels on every side (negative values grow it).
+    pub fn inset(&self, d: f32) -> Self {
+        Self::new(
+            self.x + d,
+            self.y + d,
+            self.width - 2.0 * d,
+            self.height - 2.0 * d,
+        )
+    }
+
+    /// Shrink the rectangle by per-side `insets`.
+    pub fn inset_by(&self, insets: Insets) -> Self {
+        Self::new(
+            self.x + insets.left,
+            self.y + insets.top,
+            self.width - insets.left - insets.right,
+            self.height - insets.top - insets.bottom,
+        )
+    }
+
+    /// The smallest rectangle containing both `self` and `other`.
+    pub fn union(&self, other: Rect) -> Self {
+        Self::from_ltrb(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
+    }
+
+    /// The overlap of `self` and `other`, or `None` if they don't overlap.
+    pub fn intersect(&self, other: Rect) -> Option<Self> {
+        let r = Self::from_ltrb(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.right().min(other.right()),
+            self.bottom().min(other.bottom()),
+        );
+        (r.width > 0.0 && r.height > 0.0).then_some(r)
+    }
+
+    /// Whether the point lies inside the rectangle (right/bottom edges exclusive).
+    pub fn contains(&self, p: Point) -> bool {
+        p.x >= self.x && p.x < self.right() && p.y >= self.y && p.y < self.bottom()
+    }
+}
+
+/// Per-side spacing, in pixels — padding inside a rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Insets {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Insets {
+    /// Per-side insets in CSS order: top, right, bottom, left.
+    pub const fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// The same inset on all four sides.
+    pub const fn uniform(d: f32) -> Self {
+        Self::new(d, d, d, d)
+    }
+
+    /// `vertical` above and below, `horizontal` left and right.
+    pub const fn symmetric(vertical: f32, horizontal: f32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+}
+
+impl From<f32> for Insets {
+    fn from(d: f32) -> Self {
+        Self::uniform(d)
+    }
+}
+
+/// Per-corner radii for rounded rectangles, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CornerRadii {
+    pub top_left: f32,
+    pub top_right: f32,
+    pub bottom_right: f32,
+    pub bottom_left: f32,
+}
+
+impl CornerRadii {
+    pub const fn new(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> Self {
+        Self {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+
+    /// The same radius on all four corners.
+    pub const fn uniform(r: f32) -> Self {
+        Self::new(r, r, r, r)
+    }
+}
+
+impl From<f32> for CornerRadii {
+    fn from(r: f32) -> Self {
+        Self::uniform(r)
+    }
+}
+
+/// Corner of a rect: where a [`crate::Badge`] sits, or where a
+/// [`crate::BorderStyle::Progress`] stroke starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Corner {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_edges() {
+        let r = Rect::new(10.0, 20.0, 30.0, 40.0);
+        assert_eq!(r.right(), 40.0);
+        assert_eq!(r.bottom(), 60.0);
+        assert_eq!(r.center(), Point::new(25.0, 40.0));
+        assert_eq!(Rect::from_ltrb(10.0, 20.0, 40.0, 60.0), r);
+    }
+
+    #[test]
+    fn rect_inset() {
+        let r = Rect::new(0.0, 0.0, 144.0, 144.0).inset(8.0);
+        assert_eq!(r, Rect::new(8.0, 8.0, 128.0, 128.0));
+        let r = Rect::new(0.0, 0.0, 144.0, 144.0).inset_by(Insets::new(4.0, 8.0, 12.0, 16.0));
+        assert_eq!(r, Rect::new(16.0, 4.0, 120.0, 128.0));
+    }
+
+    #[test]
+    fn rect_union() {
+        let a = Rect::new(0.0, 10.0, 20.0, 5.0);
+        let b = Rect::new(5.0, 0.0, 30.0, 8.0);
+        assert_eq!(a.union(b), Rect::from_ltrb(0.0, 0.0, 35.0, 15.0));
+    }
+
+    #[test]
+    fn rect_intersect() {
+        let a = Rect::new(0.0, 10.0, 20.0, 5.0);
+        let b = Rect::new(5.0, 0.0, 30.0, 12.0);
+        assert_eq!(a.intersect(b), Some(Rect::from_ltrb(5.0, 10.0, 20.0, 12.0)));
+        assert_eq!(a.intersect(Rect::new(20.0, 0.0, 5.0, 50.0)), None);
+    }
+}
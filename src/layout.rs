@@ -1,6 +1,6 @@
-use ab_glyph::{Font, PxScale, ScaleFont};
+use unicode_bidi::Direction;
 
-use crate::font::FontHandle;
+use crate::{font::FontHandle, shaping::shape};
 
 /// A single laid-out line of text with its pre-computed pixel width.
 ///
@@ -12,6 +12,28 @@ pub struct TextLine {
     pub width_px: f32,
 }
 
+/// Base direction of a paragraph: which side lines start from and how
+/// mixed-direction runs are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextDirection {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl TextDirection {
+    /// Direction of the first strong character in `text`, as the Unicode
+    /// Bidirectional Algorithm decides it. `None` if there is none, e.g. for
+    /// digits and punctuation only.
+    pub fn detect(text: &str) -> Option<Self> {
+        match unicode_bidi::get_base_direction(text) {
+            Direction::Ltr => Some(Self::Ltr),
+            Direction::Rtl => Some(Self::Rtl),
+            Direction::Mixed => None,
+        }
+    }
+}
+
 /// Options controlling how text is broken into lines.
 #[derive(Debug, Clone)]
 pub struct WrapOptions {
@@ -33,22 +55,10 @@ impl Default for WrapOptions {
 
 /// Measure the pixel width of a string at the given font size.
 ///
-/// Accounts for kerning between adjacent glyphs.
+/// The text is shaped with the font's OpenType features, so ligatures and GPOS
+/// kerning are accounted for exactly as [`crate::Canvas::draw_text`] draws them.
 pub fn measure_line(font: &FontHandle, scale_px: f32, text: &str) -> f32 {
-    let sf = font.arc().as_scaled(PxScale::from(scale_px));
-    let mut width = 0.0_f32;
-    let mut prev = None;
-
-    for ch in text.chars() {
-        let glyph_id = sf.glyph_id(ch);
-        if let Some(prev_id) = prev {
-            width += sf.kern(prev_id, glyph_id);
-        }
-        width += sf.h_advance(glyph_id);
-        prev = Some(glyph_id);
-    }
-
-    width
+    shape(font, scale_px, text, None).advance
 }
 
 /// Greedy word-wrap: split `text` on whitespace and accumulate words onto the
@@ -58,6 +68,8 @@ pub fn measure_line(font: &FontHandle, scale_px: f32, text: &str) -> f32 {
 /// words are concatenated onto the final line (no silent truncation).
 ///
 /// Each [`TextLine`] contains the pre-measured pixel width for alignment use.
+/// Lines keep the text's logical order; right-to-left and mixed-direction runs
+/// are reordered when the line is drawn.
 pub fn wrap_text(
     font: &FontHandle,
     scale_px: f32,
@@ -118,17 +130,23 @@ pub fn wrap_text(
 mod tests {
     use super::*;
 
-    // Basic sanity tests that don't require a real font — just check logic with
-    // zero-width glyphs (which ab_glyph returns for unknown codepoints in a dummy font).
-    // Real measurement accuracy is verified visually via the CLI.
-
     #[test]
-    fn wrap_empty() {
-        // We need a real font handle for these tests; skip if unavailable.
-        // These tests serve as documentation of expected behavior.
-        let opts = WrapOptions::default();
-        assert_eq!(opts.max_width, 130.0);
-        assert_eq!(opts.max_lines, 3);
+    fn wrap_keeps_mixed_direction_text_in_logical_order() {
+        let font = crate::test_fonts::dejavu();
+        let text = "שלום world אבג 42";
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let opts = WrapOptions {
+            max_width: measure_line(&font, 20.0, "שלום world") + 1.0,
+            max_lines: 3,
+        };
+        let lines = wrap_text(&font, 20.0, text, &opts);
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        // Logical order: the bidi reordering happens when a line is drawn.
+        assert_eq!(texts, vec!["שלום world", "אבג 42"]);
+        assert_eq!(texts.join(" "), words.join(" "));
+        for line in &lines {
+            assert_eq!(line.width_px, measure_line(&font, 20.0, &line.text));
+        }
     }
 
     #[test]
@@ -137,4 +155,44 @@ mod tests {
         assert_eq!(opts.max_lines, 3);
         assert_eq!(opts.max_width, 130.0);
     }
+
+    #[test]
+    fn direction_follows_first_strong_character() {
+        assert_eq!(
+            TextDirection::detect("123 שלום abc"),
+            Some(TextDirection::Rtl)
+        );
+        assert_eq!(
+            TextDirection::detect("(Key) مرحبا"),
+            Some(TextDirection::Ltr)
+        );
+        assert_eq!(TextDirection::detect("42 %"), None);
+    }
+
+    #[test]
+    fn wrap_measures_fallback_glyphs_in_the_fallback_font() {
+        use crate::{font::FontRegistry, test_fonts};
+        let mut fonts = FontRegistry::new();
+        fonts.load_vec("tiny", test_fonts::tiny_font()).unwrap();
+        fonts.load_bytes("dejavu", test_fonts::DEJAVU_SANS).unwrap();
+        let font = fonts.chain("tiny", &["dejavu"]).unwrap();
+
+        // `a` and `b` are one-em glyphs in the tiny font; the rest comes from
+        // DejaVu Sans, matched to the tiny font's pixels per em.
+        let fallback_px = font.face_scale(1, 20.0).y;
+        let dejavu = fonts.require("dejavu").unwrap();
+        let cd = measure_line(&dejavu, fallback_px, "cd");
+        assert!((measure_line(&font, 20.0, "ab") - 40.0).abs() < 1e-3);
+        assert!((measure_line(&font, 20.0, "cd") - cd).abs() < 1e-3);
+        assert!((cd - 40.0).abs() > 1.0);
+
+        let opts = WrapOptions {
+            max_width: 40.0 + cd / 2.0,
+            max_lines: 3,
+        };
+        let lines = wrap_text(&font, 20.0, "ab cd", &opts);
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, vec!["ab", "cd"]);
+        assert!((lines[1].width_px - cd).abs() < 1e-3);
+    }
 }
@@ -24,19 +24,62 @@
 //! // cx.sd().set_image_b64(event.context(), rendered.to_base64().unwrap());
 //! ```
 
+pub mod bitmap;
+pub mod blend;
 pub mod border;
 pub mod canvas;
 pub mod color;
+pub mod effect;
+pub mod emoji;
 pub mod error;
+pub mod filter;
 pub mod font;
+pub mod geom;
 pub mod layout;
+pub mod mask;
 pub mod output;
+pub mod paint;
+pub mod path;
+pub mod pipeline;
+#[cfg(feature = "qr")]
+pub mod qr;
+mod raster;
+pub mod rich;
+pub mod shape;
+mod shaping;
+pub mod stroke;
+#[cfg(feature = "svg")]
+pub mod svg;
+#[cfg(test)]
+mod test_fonts;
+pub mod transform;
+pub mod widgets;
 
 // Flatten the most-used items to the crate root for ergonomic imports.
-pub use border::BorderStyle;
-pub use canvas::{Canvas, HAlign, TextOptions, VAlign};
+pub use bitmap::{ImageFit, ImageOptions, ImageQuality, ImageSource};
+pub use blend::BlendMode;
+pub use border::{BorderStyle, PerimeterDirection};
+pub use canvas::{Anchor, Canvas, HAlign, TextOptions, VAlign};
 pub use color::Color;
+pub use effect::Effect;
+pub use emoji::EmojiDirectory;
 pub use error::RenderError;
-pub use font::{FontHandle, FontRegistry};
-pub use layout::{TextLine, WrapOptions, measure_line, wrap_text};
+pub use filter::Filter;
+pub use font::{FontFeature, FontHandle, FontRegistry};
+pub use geom::{Corner, CornerRadii, Insets, Point, Rect};
+pub use image::RgbaImage;
+pub use layout::{TextDirection, TextLine, WrapOptions, measure_line, wrap_text};
+pub use mask::{Mask, MaskMode};
 pub use output::RenderedImage;
+pub use paint::{ExtendMode, Gradient, GradientStop, Paint};
+pub use path::{FillRule, Path};
+pub use pipeline::Pipeline;
+#[cfg(feature = "qr")]
+pub use qr::{QrColors, QrEcc};
+pub use rich::{RichLine, RichText, Span, TextStyle, wrap_rich_text};
+pub use shape::LineCap;
+pub use stroke::{LineJoin, Stroke};
+#[cfg(feature = "svg")]
+pub use svg::SvgOptions;
+pub use transform::Transform;
+pub use widgets::{Badge, Chart, ChartKind, Gauge, Orientation, ProgressBar, ValueLabel};
@@ -2,7 +2,8 @@ use std::path::PathBuf;
 
 use clap::Parser;
 use streamdeck_render::{
-    BorderStyle, Canvas, Color, FontRegistry, TextOptions, WrapOptions, wrap_text,
+    BorderStyle, Canvas, Color, Corner, EmojiDirectory, FontFeature, FontRegistry, LineCap,
+    PerimeterDirection, RichText, TextOptions, TextStyle, WrapOptions, wrap_rich_text, wrap_text,
 };
 
 /// Render a Stream Deck button icon with custom text and a TrueType/OpenType font.
@@ -28,6 +29,22 @@ struct Cli {
     #[arg(long, default_value_t = 28.0)]
     size: f32,
 
+    /// OpenType feature to apply, e.g. `tnum`, `-liga` or `ss01=1`. Repeatable.
+    #[arg(long = "feature", value_name = "TAG", allow_hyphen_values = true)]
+    features: Vec<String>,
+
+    /// Treat `--text` as markup: `[b]`, `[size=40]`, `[color=#ff0000]`, `[sup]`, `[u]`, …
+    #[arg(long)]
+    markup: bool,
+
+    /// Font used for `[b]` in markup.
+    #[arg(long, value_name = "PATH")]
+    bold_font: Option<PathBuf>,
+
+    /// Folder of Twemoji-style emoji PNGs (`1f44d.png`, `1f469-200d-1f4bb.png`, …).
+    #[arg(long, value_name = "PATH")]
+    emoji_dir: Option<PathBuf>,
+
     /// Canvas dimensions as WxH (e.g. `144x144` or `72x72`).
     #[arg(long, default_value = "144x144")]
     canvas: String,
@@ -45,7 +62,7 @@ struct Cli {
     #[arg(long, default_value_t = 3)]
     max_lines: usize,
 
-    /// Border style: `none`, `solid`, or `vignette`.
+    /// Border style: `none`, `solid`, `dashed`, `dotted`, `double`, `progress`, or `vignette`.
     #[arg(long, default_value = "none")]
     border: String,
 
@@ -53,14 +70,31 @@ struct Cli {
     #[arg(long, default_value = "#ffffffff")]
     border_color: String,
 
-    /// Stroke thickness in pixels (only used when `--border solid`).
+    /// Stroke thickness in pixels, or dot diameter for `--border dotted`.
     #[arg(long, default_value_t = 4.0)]
     border_thickness: f32,
 
-    /// Corner radius in pixels (used by both solid and vignette borders).
+    /// Corner radius in pixels (used by every border style).
     #[arg(long, default_value_t = 8.0)]
     border_radius: f32,
 
+    /// Dash length in pixels (only used when `--border dashed`).
+    #[arg(long, default_value_t = 10.0)]
+    border_dash: f32,
+
+    /// Space between dashes, dots, or the two strokes of a double border, in pixels.
+    #[arg(long, default_value_t = 4.0)]
+    border_gap: f32,
+
+    /// Share of the frame stroked by `--border progress`, from 0.0 to 1.0. The
+    /// stroke starts at the top-left corner and runs clockwise.
+    #[arg(long, default_value_t = 1.0)]
+    border_progress: f32,
+
+    /// Distance of the border from the canvas edge in pixels.
+    #[arg(long, default_value_t = 0.0)]
+    border_inset: f32,
+
     /// Vignette fade width in pixels (only used when `--border vignette`).
     #[arg(long, default_value_t = 10.0)]
     vignette_width: f32,
@@ -99,9 +133,23 @@ fn run(cli: Cli) -> anyhow::Result<()> {
 
     // ── Load font ─────────────────────────────────────────────────────────────
     let mut fonts = FontRegistry::new();
-    let font = fonts.load_file("main", &cli.font).map_err(|e| {
-        anyhow::anyhow!("failed to load font '{}': {e}", cli.font.display())
+    let font = fonts
+        .load_file("main", &cli.font)
+        .map_err(|e| anyhow::anyhow!("failed to load font '{}': {e}", cli.font.display()))?;
+    let font = cli.features.iter().try_fold(font, |font, s| {
+        FontFeature::parse(s)
+            .map(|f| font.feature(f))
+            .ok_or_else(|| anyhow::anyhow!("invalid font feature '{s}'"))
     })?;
+    let font = match &cli.emoji_dir {
+        Some(dir) => font.emoji_images(&EmojiDirectory::open(dir)?),
+        None => font,
+    };
+    if let Some(path) = &cli.bold_font {
+        fonts
+            .load_file("bold", path)
+            .map_err(|e| anyhow::anyhow!("failed to load font '{}': {e}", path.display()))?;
+    }
 
     // ── Build canvas ──────────────────────────────────────────────────────────
     let mut canvas = Canvas::new(w, h);
@@ -119,16 +167,25 @@ fn run(cli: Cli) -> anyhow::Result<()> {
         max_lines: cli.max_lines,
     };
 
-    // Process hard line breaks: split on '\n', wrap each segment independently,
-    // then concatenate all resulting lines.
-    let lines: Vec<_> = text
-        .split('\n')
-        .flat_map(|segment| wrap_text(&font, cli.size, segment, &wrap_opts))
-        .collect();
+    let opts = TextOptions::new(font.clone(), cli.size).color(text_color);
+    if cli.markup {
+        let base = TextStyle::new(font, cli.size).color(text_color);
+        let rich = RichText::parse(&text, &base, &fonts)?;
+        canvas
+            .draw_rich_text(&wrap_rich_text(&rich, &wrap_opts), &opts)
+            .map_err(|e| anyhow::anyhow!("text rendering failed: {e}"))?;
+    } else {
+        // Process hard line breaks: split on '\n', wrap each segment
+        // independently, then concatenate all resulting lines.
+        let lines: Vec<_> = text
+            .split('\n')
+            .flat_map(|segment| wrap_text(&font, cli.size, segment, &wrap_opts))
+            .collect();
 
-    canvas
-        .draw_text(&lines, &TextOptions::new(font, cli.size).color(text_color))
-        .map_err(|e| anyhow::anyhow!("text rendering failed: {e}"))?;
+        canvas
+            .draw_text(&lines, &opts)
+            .map_err(|e| anyhow::anyhow!("text rendering failed: {e}"))?;
+    }
 
     // ── Border ────────────────────────────────────────────────────────────────
     let border_style = match cli.border.as_str() {
@@ -136,16 +193,46 @@ fn run(cli: Cli) -> anyhow::Result<()> {
         "solid" => BorderStyle::Solid {
             thickness: cli.border_thickness,
             radius: cli.border_radius,
-            color: border_color,
+            color: border_color.into(),
+        },
+        "dashed" => BorderStyle::Dashed {
+            thickness: cli.border_thickness,
+            radius: cli.border_radius,
+            dash: cli.border_dash,
+            gap: cli.border_gap,
+            color: border_color.into(),
+        },
+        "dotted" => BorderStyle::Dotted {
+            size: cli.border_thickness,
+            radius: cli.border_radius,
+            gap: cli.border_gap,
+            color: border_color.into(),
+        },
+        "double" => BorderStyle::Double {
+            thickness: cli.border_thickness,
+            gap: cli.border_gap,
+            radius: cli.border_radius,
+            color: border_color.into(),
+        },
+        "progress" => BorderStyle::Progress {
+            thickness: cli.border_thickness,
+            radius: cli.border_radius,
+            fraction: cli.border_progress,
+            start: Corner::TopLeft,
+            direction: PerimeterDirection::Clockwise,
+            cap: LineCap::Round,
+            color: border_color.into(),
         },
         "vignette" => BorderStyle::Vignette {
             width: cli.vignette_width,
             radius: cli.border_radius,
-            color: border_color,
+            color: border_color.into(),
         },
-        other => anyhow::bail!("unknown border style '{other}' — choose none, solid, or vignette"),
+        other => anyhow::bail!(
+            "unknown border style '{other}' — choose none, solid, dashed, dotted, double, progress, or vignette"
+        ),
     };
-    canvas.draw_border(&border_style);
+    canvas.draw_border(&border_style.inset(cli.border_inset));
 
     // ── Save ──────────────────────────────────────────────────────────────────
     let rendered = canvas.finish();
@@ -0,0 +1,155 @@
+use crate::{canvas::Canvas, error::RenderError};
+
+/// Whether a [`Mask`] keeps what it covers or what it doesn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskMode {
+    /// Keep pixels where the mask is opaque.
+    #[default]
+    Normal,
+    /// Keep pixels where the mask is transparent — for knock-outs.
+    Inverted,
+}
+
+/// A per-pixel alpha mask in canvas coordinates.
+///
+/// Build one from anything a [`Canvas`] can draw — text, shapes, paths, images —
+/// then clip to it with [`Canvas::push_clip_mask`] or cut it out of existing
+/// content with [`Canvas::apply_mask`]. Only the drawn alpha is kept.
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{Canvas, Color, Mask, MaskMode};
+///
+/// // Knock the label out of a filled background.
+/// let label = Mask::render(144, 144, |c| c.draw_text(&lines, &opts))?;
+/// canvas.fill(Color::rgb(30, 120, 255));
+/// canvas.apply_mask(&label, MaskMode::Inverted);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mask {
+    width: u32,
+    height: u32,
+    data: Vec<f32>,
+}
+
+impl Mask {
+    /// Take the alpha channel of everything drawn on `canvas` so far.
+    pub fn from_canvas(canvas: &Canvas) -> Self {
+        Self {
+            width: canvas.width(),
+            height: canvas.height(),
+            data: canvas.alpha(),
+        }
+    }
+
+    /// Draw into a fresh transparent `width × height` canvas with `f` and keep
+    /// the resulting alpha.
+    pub fn render(
+        width: u32,
+        height: u32,
+        f: impl FnOnce(&mut Canvas) -> Result<(), RenderError>,
+    ) -> Result<Self, RenderError> {
+        let mut canvas = Canvas::new(width, height);
+        f(&mut canvas)?;
+        Ok(Self::from_canvas(&canvas))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Mask value at pixel `(px, py)` for `mode`. Outside the mask counts as transparent.
+    pub(crate) fn value(&self, px: u32, py: u32, mode: MaskMode) -> f32 {
+        let v = if px < self.width && py < self.height {
+            self.data[(py * self.width + px) as usize]
+        } else {
+            0.0
+        };
+        match mode {
+            MaskMode::Normal => v,
+            MaskMode::Inverted => 1.0 - v,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        geom::{Point, Rect},
+        path::{FillRule, Path},
+    };
+
+    fn dot() -> Mask {
+        Mask::render(20, 20, |c| {
+            c.fill_rect(Rect::new(5.0, 5.0, 10.0, 10.0), Color::WHITE);
+            Ok(())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn values_follow_mode() {
+        let m = dot();
+        assert_eq!(m.value(10, 10, MaskMode::Normal), 1.0);
+        assert_eq!(m.value(1, 1, MaskMode::Normal), 0.0);
+        assert_eq!(m.value(1, 1, MaskMode::Inverted), 1.0);
+        assert_eq!(m.value(50, 50, MaskMode::Inverted), 1.0);
+    }
+
+    #[test]
+    fn clip_rect_limits_draws_until_popped() {
+        let mut c = Canvas::new(20, 20);
+        c.push_clip_rect(Rect::new(0.0, 0.0, 10.0, 20.0));
+        c.fill_rect(Rect::new(0.0, 0.0, 20.0, 10.0), Color::WHITE);
+        c.pop_clip();
+        c.fill_rect(Rect::new(0.0, 10.0, 20.0, 10.0), Color::WHITE);
+
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(5, 5)[3], 255);
+        assert_eq!(img.get_pixel(15, 5)[3], 0);
+        assert_eq!(img.get_pixel(15, 15)[3], 255);
+    }
+
+    #[test]
+    fn nested_clips_intersect() {
+        let mut c = Canvas::new(20, 20);
+        c.push_clip_rect(Rect::new(0.0, 0.0, 10.0, 20.0));
+        c.push_clip_path(
+            &Path::new().rect(Rect::new(0.0, 0.0, 20.0, 10.0)),
+            FillRule::NonZero,
+        );
+        c.fill(Color::WHITE);
+
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(5, 5)[3], 255);
+        assert_eq!(img.get_pixel(5, 15)[3], 0);
+        assert_eq!(img.get_pixel(15, 5)[3], 0);
+    }
+
+    #[test]
+    fn inverted_mask_knocks_out() {
+        let mut c = Canvas::new(20, 20);
+        c.fill(Color::WHITE);
+        c.apply_mask(&dot(), MaskMode::Inverted);
+
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(10, 10)[3], 0);
+        assert_eq!(img.get_pixel(1, 1)[3], 255);
+    }
+
+    #[test]
+    fn clip_mask_keeps_covered_pixels() {
+        let mut c = Canvas::new(20, 20);
+        c.push_clip_mask(&dot(), MaskMode::Normal);
+        c.fill_circle(Point::new(10.0, 10.0), 20.0, Color::WHITE);
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(10, 10)[3], 255);
+        assert_eq!(img.get_pixel(2, 2)[3], 0);
+    }
+}
@@ -0,0 +1,337 @@
+use std::f32::consts::PI;
+
+use crate::{color::Color, geom::Point};
+
+/// 4×4 ordered-dither thresholds used to break up gradient banding.
+const BAYER_4X4: [[f32; 4]; 4] = [
+    [0.0, 8.0, 2.0, 10.0],
+    [12.0, 4.0, 14.0, 6.0],
+    [3.0, 11.0, 1.0, 9.0],
+    [15.0, 7.0, 13.0, 5.0],
+];
+
+/// What a gradient does outside its `[0, 1]` range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExtendMode {
+    /// Repeat the first/last stop color.
+    #[default]
+    Pad,
+    /// Tile the gradient.
+    Repeat,
+    /// Tile the gradient, mirroring every other copy.
+    Reflect,
+}
+
+/// A color at a position along a gradient, with `offset` in `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+/// Color stops shared by every gradient kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    /// Stops sorted by offset. Colors are interpolated with premultiplied alpha so
+    /// fades to transparent don't darken.
+    pub stops: Vec<GradientStop>,
+    pub extend: ExtendMode,
+    /// Apply ordered dithering when quantizing to 8 bits, hiding banding in
+    /// slow gradients.
+    pub dither: bool,
+}
+
+impl Gradient {
+    /// Build a gradient from `(offset, color)` pairs. Stops are sorted by offset.
+    pub fn new(stops: &[(f32, Color)]) -> Self {
+        let mut stops: Vec<_> = stops
+            .iter()
+            .map(|&(offset, color)| GradientStop { offset, color })
+            .collect();
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        Self {
+            stops,
+            extend: ExtendMode::Pad,
+            dither: false,
+        }
+    }
+
+    /// Sample the gradient at parameter `t`, returning straight-alpha channels in `[0, 1]`.
+    fn sample(&self, t: f32) -> [f32; 4] {
+        let t = match self.extend {
+            ExtendMode::Pad => t.clamp(0.0, 1.0),
+            ExtendMode::Repeat => t.rem_euclid(1.0),
+            ExtendMode::Reflect => {
+                let m = t.rem_euclid(2.0);
+                if m > 1.0 { 2.0 - m } else { m }
+            }
+        };
+
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return [0.0; 4];
+        };
+        if t <= first.offset {
+            return unit_rgba(first.color);
+        }
+        if t >= last.offset {
+            return unit_rgba(last.color);
+        }
+
+        let i = self.stops.partition_point(|s| s.offset <= t).max(1);
+        let (a, b) = (&self.stops[i - 1], &self.stops[i]);
+        let span = b.offset - a.offset;
+        let f = if span > 0.0 {
+            (t - a.offset) / span
+        } else {
+            1.0
+        };
+
+        // Interpolate premultiplied, then un-premultiply.
+        let ca = unit_rgba(a.color);
+        let cb = unit_rgba(b.color);
+        let alpha = ca[3] + (cb[3] - ca[3]) * f;
+        if alpha <= 0.0 {
+            return [0.0; 4];
+        }
+        let mix = |i: usize| (ca[i] * ca[3] + (cb[i] * cb[3] - ca[i] * ca[3]) * f) / alpha;
+        [mix(0), mix(1), mix(2), alpha]
+    }
+}
+
+/// The source of color for a fill, stroke or text draw: a solid color or a gradient.
+///
+/// Gradient geometry is given in canvas pixel coordinates and follows the canvas
+/// transform. Anywhere a [`Color`] is accepted a `Paint` is too, via `From<Color>`.
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{Color, Paint, Point};
+///
+/// let sunset = Paint::linear(
+///     Point::new(0.0, 0.0),
+///     Point::new(0.0, 144.0),
+///     &[(0.0, Color::rgb(255, 140, 0)), (1.0, Color::rgb(120, 0, 160))],
+/// )
+/// .dither(true);
+/// canvas.fill(sunset);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    Solid(Color),
+
+    /// Colors vary along the line from `start` (offset 0) to `end` (offset 1).
+    LinearGradient {
+        start: Point,
+        end: Point,
+        gradient: Gradient,
+    },
+
+    /// Colors vary with distance from `center`, reaching offset 1 at `radius`.
+    RadialGradient {
+        center: Point,
+        radius: f32,
+        gradient: Gradient,
+    },
+
+    /// Colors sweep around `center`, starting at `angle` radians (0 = +X) and
+    /// turning clockwise on screen through one full revolution.
+    ConicGradient {
+        center: Point,
+        angle: f32,
+        gradient: Gradient,
+    },
+}
+
+impl Paint {
+    pub fn linear(start: Point, end: Point, stops: &[(f32, Color)]) -> Self {
+        Self::LinearGradient {
+            start,
+            end,
+            gradient: Gradient::new(stops),
+        }
+    }
+
+    pub fn radial(center: Point, radius: f32, stops: &[(f32, Color)]) -> Self {
+        Self::RadialGradient {
+            center,
+            radius,
+            gradient: Gradient::new(stops),
+        }
+    }
+
+    pub fn conic(center: Point, angle: f32, stops: &[(f32, Color)]) -> Self {
+        Self::ConicGradient {
+            center,
+            angle,
+            gradient: Gradient::new(stops),
+        }
+    }
+
+    /// Set the extend mode of a gradient. No effect on solid paints.
+    pub fn extend(mut self, mode: ExtendMode) -> Self {
+        if let Some(g) = self.gradient_mut() {
+            g.extend = mode;
+        }
+        self
+    }
+
+    /// Enable or disable dithering of a gradient. No effect on solid paints.
+    pub fn dither(mut self, on: bool) -> Self {
+        if let Some(g) = self.gradient_mut() {
+            g.dither = on;
+        }
+        self
+    }
+
+    /// Color of this paint at canvas pixel `(px, py)`, sampled at the pixel center.
+    pub(crate) fn color_at(&self, px: i32, py: i32) -> Color {
+        self.color_at_point(px as f32 + 0.5, py as f32 + 0.5, px, py)
+    }
+
+    /// Color of this paint at `(x, y)` in gradient space, for device pixel
+    /// `(px, py)` (which only picks the dither threshold).
+    pub(crate) fn color_at_point(&self, x: f32, y: f32, px: i32, py: i32) -> Color {
+        let (t, gradient) = match self {
+            Paint::Solid(c) => return *c,
+            Paint::LinearGradient {
+                start,
+                end,
+                gradient,
+            } => {
+                let (dx, dy) = (end.x - start.x, end.y - start.y);
+                let len_sq = dx * dx + dy * dy;
+                let t = if len_sq > 0.0 {
+                    ((x - start.x) * dx + (y - start.y) * dy) / len_sq
+                } else {
+                    0.0
+                };
+                (t, gradient)
+            }
+            Paint::RadialGradient {
+                center,
+                radius,
+                gradient,
+            } => {
+                let d = f32::hypot(x - center.x, y - center.y);
+                let t = if *radius > 0.0 { d / radius } else { 1.0 };
+                (t, gradient)
+            }
+            Paint::ConicGradient {
+                center,
+                angle,
+                gradient,
+            } => {
+                let a = (y - center.y).atan2(x - center.x) - angle;
+                (a.rem_euclid(2.0 * PI) / (2.0 * PI), gradient)
+            }
+        };
+
+        let c = gradient.sample(t);
+        let offset = if gradient.dither {
+            (BAYER_4X4[py.rem_euclid(4) as usize][px.rem_euclid(4) as usize] + 0.5) / 16.0 - 0.5
+        } else {
+            0.0
+        };
+        let q = |v: f32| (v * 255.0 + offset).round().clamp(0.0, 255.0) as u8;
+        Color::rgba(q(c[0]), q(c[1]), q(c[2]), q(c[3]))
+    }
+
+    fn gradient_mut(&mut self) -> Option<&mut Gradient> {
+        match self {
+            Paint::Solid(_) => None,
+            Paint::LinearGradient { gradient, .. }
+            | Paint::RadialGradient { gradient, .. }
+            | Paint::ConicGradient { gradient, .. } => Some(gradient),
+        }
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(c: Color) -> Self {
+        Paint::Solid(c)
+    }
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Paint::Solid(Color::WHITE)
+    }
+}
+
+fn unit_rgba(c: Color) -> [f32; 4] {
+    [
+        c.r as f32 / 255.0,
+        c.g as f32 / 255.0,
+        c.b as f32 / 255.0,
+        c.a as f32 / 255.0,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+
+    #[test]
+    fn solid_is_constant() {
+        let p = Paint::from(RED);
+        assert_eq!(p.color_at(0, 0), RED);
+        assert_eq!(p.color_at(100, 7), RED);
+    }
+
+    #[test]
+    fn linear_endpoints_and_midpoint() {
+        let p = Paint::linear(
+            Point::new(0.0, 0.0),
+            Point::new(100.0, 0.0),
+            &[(0.0, RED), (1.0, BLUE)],
+        );
+        assert_eq!(p.color_at(-10, 0), RED);
+        assert_eq!(p.color_at(200, 0), BLUE);
+        let mid = p.color_at(49, 0); // pixel center at x = 49.5
+        assert!(
+            mid.r.abs_diff(128) <= 1 && mid.b.abs_diff(127) <= 1,
+            "{mid:?}"
+        );
+    }
+
+    #[test]
+    fn extend_modes() {
+        let stops = [(0.0, RED), (1.0, BLUE)];
+        let base = Paint::linear(Point::new(0.0, 0.0), Point::new(10.0, 0.0), &stops);
+        // x = 12.5 → t = 1.25
+        assert_eq!(base.clone().color_at(12, 0), BLUE);
+        let repeat = base.clone().extend(ExtendMode::Repeat).color_at(12, 0);
+        assert!(repeat.r > repeat.b, "repeat restarts near red: {repeat:?}");
+        let reflect = base.extend(ExtendMode::Reflect).color_at(12, 0);
+        assert!(
+            reflect.b > reflect.r,
+            "reflect stays near blue: {reflect:?}"
+        );
+    }
+
+    #[test]
+    fn fade_to_transparent_keeps_hue() {
+        let p = Paint::linear(
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            &[(0.0, RED), (1.0, Color::TRANSPARENT)],
+        );
+        let c = p.color_at(5, 0);
+        assert_eq!((c.r, c.g, c.b), (255, 0, 0));
+        assert!(c.a > 0 && c.a < 255);
+    }
+
+    #[test]
+    fn conic_sweeps_clockwise() {
+        let p = Paint::conic(Point::new(0.5, 0.5), 0.0, &[(0.0, RED), (1.0, BLUE)]);
+        // Directly below the center is a quarter turn clockwise from +X.
+        let c = p.color_at(0, 10);
+        assert!(c.r > c.b);
+        // Directly above is three quarters of the way round.
+        let c = p.color_at(0, -10);
+        assert!(c.b > c.r);
+    }
+}
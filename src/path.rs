@@ -0,0 +1,490 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::{
+    geom::{CornerRadii, Point, Rect},
+    transform::Transform,
+};
+
+/// Maximum distance in pixels between a curve and its flattened polyline.
+pub(crate) const FLATTEN_TOLERANCE: f32 = 0.1;
+
+/// How the interior of a self-intersecting or nested path is determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FillRule {
+    /// A point is inside if the winding number around it is non-zero.
+    #[default]
+    NonZero,
+    /// A point is inside if a ray from it crosses an odd number of edges.
+    /// Nested subpaths alternate between filled and hollow regardless of direction.
+    EvenOdd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PathCmd {
+    MoveTo(Point),
+    LineTo(Point),
+    QuadTo(Point, Point),
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+/// A vector path made of lines and Bézier curves, in canvas pixel coordinates
+/// (before the canvas transform is applied).
+///
+/// Build with chained calls, then draw with [`crate::Canvas::fill_path`] or
+/// [`crate::Canvas::stroke_path`].
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{FillRule, Path};
+///
+/// // A play triangle.
+/// let play = Path::new()
+///     .move_to(52.0, 40.0)
+///     .line_to(104.0, 72.0)
+///     .line_to(52.0, 104.0)
+///     .close();
+/// canvas.fill_path(&play, FillRule::NonZero, Color::WHITE);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    pub(crate) cmds: Vec<PathCmd>,
+    start: Option<Point>,
+    current: Option<Point>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the path contains no drawing commands.
+    pub fn is_empty(&self) -> bool {
+        self.cmds.is_empty()
+    }
+
+    /// The current pen position, if any command has been issued.
+    pub fn current_point(&self) -> Option<Point> {
+        self.current
+    }
+
+    /// Start a new subpath at `(x, y)`.
+    pub fn move_to(mut self, x: f32, y: f32) -> Self {
+        let p = Point::new(x, y);
+        self.cmds.push(PathCmd::MoveTo(p));
+        self.start = Some(p);
+        self.current = Some(p);
+        self
+    }
+
+    /// Draw a straight line to `(x, y)`. Starts a subpath there if none is open.
+    pub fn line_to(mut self, x: f32, y: f32) -> Self {
+        if self.current.is_none() {
+            return self.move_to(x, y);
+        }
+        let p = Point::new(x, y);
+        self.cmds.push(PathCmd::LineTo(p));
+        self.current = Some(p);
+        self
+    }
+
+    /// Draw a quadratic Bézier curve with control point `(cx, cy)` ending at `(x, y)`.
+    pub fn quad_to(mut self, cx: f32, cy: f32, x: f32, y: f32) -> Self {
+        self = self.ensure_start(cx, cy);
+        let p = Point::new(x, y);
+        self.cmds.push(PathCmd::QuadTo(Point::new(cx, cy), p));
+        self.current = Some(p);
+        self
+    }
+
+    /// Draw a cubic Bézier curve with control points `(c1x, c1y)`, `(c2x, c2y)`
+    /// ending at `(x, y)`.
+    pub fn cubic_to(mut self, c1x: f32, c1y: f32, c2x: f32, c2y: f32, x: f32, y: f32) -> Self {
+        self = self.ensure_start(c1x, c1y);
+        let p = Point::new(x, y);
+        self.cmds.push(PathCmd::CubicTo(
+            Point::new(c1x, c1y),
+            Point::new(c2x, c2y),
+            p,
+        ));
+        self.current = Some(p);
+        self
+    }
+
+    /// Draw a circular arc of `radius` tangent to the line from the current point
+    /// to `(x1, y1)` and to the line from `(x1, y1)` to `(x2, y2)`.
+    ///
+    /// Matches the HTML canvas `arcTo`: a straight line connects the current point
+    /// to the start of the arc. Handy for rounding the corners of arbitrary polygons.
+    pub fn arc_to(self, x1: f32, y1: f32, x2: f32, y2: f32, radius: f32) -> Self {
+        let Some(p0) = self.current else {
+            return self.move_to(x1, y1);
+        };
+        let p1 = Point::new(x1, y1);
+
+        let (v1x, v1y) = (p0.x - x1, p0.y - y1);
+        let (v2x, v2y) = (x2 - x1, y2 - y1);
+        let l1 = f32::hypot(v1x, v1y);
+        let l2 = f32::hypot(v2x, v2y);
+        let cross = v1x * v2y - v1y * v2x;
+
+        if radius <= 0.0 || l1 == 0.0 || l2 == 0.0 || cross.abs() < 1e-6 * l1 * l2 {
+            return self.line_to(x1, y1);
+        }
+
+        let (u1x, u1y) = (v1x / l1, v1y / l1);
+        let (u2x, u2y) = (v2x / l2, v2y / l2);
+        // Angle between the two legs at p1.
+        let theta = (u1x * u2x + u1y * u2y).clamp(-1.0, 1.0).acos();
+        let tangent_len = radius / (theta / 2.0).tan();
+        let t1 = Point::new(p1.x + u1x * tangent_len, p1.y + u1y * tangent_len);
+        let t2 = Point::new(p1.x + u2x * tangent_len, p1.y + u2y * tangent_len);
+
+        let (bx, by) = (u1x + u2x, u1y + u2y);
+        let bl = f32::hypot(bx, by);
+        let center_dist = radius / (theta / 2.0).sin();
+        let c = Point::new(p1.x + bx / bl * center_dist, p1.y + by / bl * center_dist);
+
+        let a0 = (t1.y - c.y).atan2(t1.x - c.x);
+        let a1 = (t2.y - c.y).atan2(t2.x - c.x);
+        let mut sweep = a1 - a0;
+        if sweep > PI {
+            sweep -= 2.0 * PI;
+        } else if sweep < -PI {
+            sweep += 2.0 * PI;
+        }
+
+        self.line_to(t1.x, t1.y)
+            .arc_segment(c, radius, radius, a0, sweep)
+    }
+
+    /// Draw a circular arc centered at `(cx, cy)` from `start` to `end` (radians).
+    ///
+    /// Angle zero points along +X. Because canvas Y grows downward, increasing
+    /// angles sweep **clockwise** on screen; pass `end < start` to sweep
+    /// counter-clockwise. A line connects the current point to the arc start.
+    /// Like HTML canvas `arc`, a sweep beyond a full turn draws the whole
+    /// circle; non-finite angles are ignored.
+    pub fn arc(self, cx: f32, cy: f32, radius: f32, start: f32, end: f32) -> Self {
+        if !start.is_finite() || !end.is_finite() {
+            return self;
+        }
+        let c = Point::new(cx, cy);
+        let sx = cx + radius * start.cos();
+        let sy = cy + radius * start.sin();
+        let path = if self.current.is_some() {
+            self.line_to(sx, sy)
+        } else {
+            self.move_to(sx, sy)
+        };
+        path.arc_segment(c, radius, radius, start, (end - start).clamp(-TAU, TAU))
+    }
+
+    /// Close the current subpath with a straight line back to its start.
+    pub fn close(mut self) -> Self {
+        if self.current.is_some() {
+            self.cmds.push(PathCmd::Close);
+            self.current = self.start;
+        }
+        self
+    }
+
+    /// Append a closed rectangle subpath.
+    pub fn rect(self, rect: Rect) -> Self {
+        self.move_to(rect.x, rect.y)
+            .line_to(rect.right(), rect.y)
+            .line_to(rect.right(), rect.bottom())
+            .line_to(rect.x, rect.bottom())
+            .close()
+    }
+
+    /// Append a closed rounded-rectangle subpath. Radii are clamped to half the
+    /// shorter side.
+    pub fn rounded_rect(self, rect: Rect, radii: impl Into<CornerRadii>) -> Self {
+        let radii = radii.into();
+        let max_r = (rect.width.min(rect.height) / 2.0).max(0.0);
+        let tl = radii.top_left.clamp(0.0, max_r);
+        let tr = radii.top_right.clamp(0.0, max_r);
+        let br = radii.bottom_right.clamp(0.0, max_r);
+        let bl = radii.bottom_left.clamp(0.0, max_r);
+        let (l, t, r, b) = (rect.x, rect.y, rect.right(), rect.bottom());
+
+        self.move_to(l + tl, t)
+            .line_to(r - tr, t)
+            .arc_segment(Point::new(r - tr, t + tr), tr, tr, -FRAC_PI_2, FRAC_PI_2)
+            .line_to(r, b - br)
+            .arc_segment(Point::new(r - br, b - br), br, br, 0.0, FRAC_PI_2)
+            .line_to(l + bl, b)
+            .arc_segment(Point::new(l + bl, b - bl), bl, bl, FRAC_PI_2, FRAC_PI_2)
+            .line_to(l, t + tl)
+            .arc_segment(Point::new(l + tl, t + tl), tl, tl, PI, FRAC_PI_2)
+            .close()
+    }
+
+    /// Append a closed circle subpath.
+    pub fn circle(self, center: Point, radius: f32) -> Self {
+        self.ellipse(center, radius, radius)
+    }
+
+    /// Append a closed axis-aligned ellipse subpath.
+    pub fn ellipse(self, center: Point, rx: f32, ry: f32) -> Self {
+        self.move_to(center.x + rx, center.y)
+            .arc_segment(center, rx, ry, 0.0, 2.0 * PI)
+            .close()
+    }
+
+    /// Bounding box of all points and control points in the path.
+    pub fn bounds(&self) -> Rect {
+        let mut min = Point::new(f32::INFINITY, f32::INFINITY);
+        let mut max = Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        let mut add = |p: &Point| {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        };
+        for cmd in &self.cmds {
+            match cmd {
+                PathCmd::MoveTo(p) | PathCmd::LineTo(p) => add(p),
+                PathCmd::QuadTo(c, p) => {
+                    add(c);
+                    add(p);
+                }
+                PathCmd::CubicTo(c1, c2, p) => {
+                    add(c1);
+                    add(c2);
+                    add(p);
+                }
+                PathCmd::Close => {}
+            }
+        }
+        if min.x > max.x {
+            return Rect::default();
+        }
+        Rect::from_ltrb(min.x, min.y, max.x, max.y)
+    }
+
+    /// Append all subpaths of `other` after this path's.
+    pub(crate) fn append(mut self, other: Path) -> Self {
+        if other.is_empty() {
+            return self;
+        }
+        self.cmds.extend(other.cmds);
+        self.start = other.start;
+        self.current = other.current;
+        self
+    }
+
+    /// Map every point of the path through `t`. Affine transforms map Bézier
+    /// curves onto Bézier curves, so this is exact.
+    pub fn transform(mut self, t: Transform) -> Self {
+        let map = |p: &mut Point| *p = t.map_point(*p);
+        for cmd in &mut self.cmds {
+            match cmd {
+                PathCmd::MoveTo(p) | PathCmd::LineTo(p) => map(p),
+                PathCmd::QuadTo(c, p) => {
+                    map(c);
+                    map(p);
+                }
+                PathCmd::CubicTo(c1, c2, p) => {
+                    map(c1);
+                    map(c2);
+                    map(p);
+                }
+                PathCmd::Close => {}
+            }
+        }
+        self.start.as_mut().map(map);
+        self.current.as_mut().map(map);
+        self
+    }
+
+    // ── private helpers ─────────────────────────────────────────────────────
+
+    /// Curves need a start point; fall back to the first control point like SVG does.
+    fn ensure_start(self, x: f32, y: f32) -> Self {
+        if self.current.is_none() {
+            self.move_to(x, y)
+        } else {
+            self
+        }
+    }
+
+    /// Append an elliptical arc as cubic Béziers, assuming the pen is already at
+    /// the arc start. Each piece spans at most a quarter turn.
+    fn arc_segment(mut self, c: Point, rx: f32, ry: f32, start: f32, sweep: f32) -> Self {
+        if sweep == 0.0 || !sweep.is_finite() || rx <= 0.0 || ry <= 0.0 {
+            return self;
+        }
+        let pieces = (sweep.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep / pieces as f32;
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+
+        let mut a = start;
+        for _ in 0..pieces {
+            let b = a + step;
+            let (sa, ca) = a.sin_cos();
+            let (sb, cb) = b.sin_cos();
+            self = self.cubic_to(
+                c.x + rx * (ca - k * sa),
+                c.y + ry * (sa + k * ca),
+                c.x + rx * (cb + k * sb),
+                c.y + ry * (sb - k * cb),
+                c.x + rx * cb,
+                c.y + ry * sb,
+            );
+            a = b;
+        }
+        self
+    }
+}
+
+/// A flattened subpath: a sequence of points joined by straight segments.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Polyline {
+    pub points: Vec<Point>,
+    pub closed: bool,
+}
+
+/// Convert every curve in `path` into line segments within [`FLATTEN_TOLERANCE`].
+pub(crate) fn flatten(path: &Path) -> Vec<Polyline> {
+    flatten_with_tolerance(path, FLATTEN_TOLERANCE)
+}
+
+/// [`flatten`] with a custom maximum deviation, for paths that will be scaled
+/// after flattening.
+pub(crate) fn flatten_with_tolerance(path: &Path, tolerance: f32) -> Vec<Polyline> {
+    let mut out = Vec::new();
+    let mut current: Vec<Point> = Vec::new();
+
+    // A lone `move_to` draws nothing, so subpaths need at least two points.
+    let flush = |current: &mut Vec<Point>, out: &mut Vec<Polyline>, closed: bool| {
+        if current.len() > 1 {
+            out.push(Polyline {
+                points: std::mem::take(current),
+                closed,
+            });
+        }
+        current.clear();
+    };
+
+    for cmd in &path.cmds {
+        match *cmd {
+            PathCmd::MoveTo(p) => {
+                flush(&mut current, &mut out, false);
+                current.push(p);
+            }
+            PathCmd::LineTo(p) => current.push(p),
+            PathCmd::QuadTo(c, p) => {
+                let p0 = *current.last().unwrap_or(&c);
+                let dd = f32::hypot(p0.x - 2.0 * c.x + p.x, p0.y - 2.0 * c.y + p.y);
+                let n = segment_count(dd / (8.0 * tolerance));
+                for i in 1..=n {
+                    let t = i as f32 / n as f32;
+                    let mt = 1.0 - t;
+                    current.push(Point::new(
+                        mt * mt * p0.x + 2.0 * mt * t * c.x + t * t * p.x,
+                        mt * mt * p0.y + 2.0 * mt * t * c.y + t * t * p.y,
+                    ));
+                }
+            }
+            PathCmd::CubicTo(c1, c2, p) => {
+                let p0 = *current.last().unwrap_or(&c1);
+                let dd1 = f32::hypot(p0.x - 2.0 * c1.x + c2.x, p0.y - 2.0 * c1.y + c2.y);
+                let dd2 = f32::hypot(c1.x - 2.0 * c2.x + p.x, c1.y - 2.0 * c2.y + p.y);
+                let n = segment_count(0.75 * dd1.max(dd2) / tolerance);
+                for i in 1..=n {
+                    let t = i as f32 / n as f32;
+                    let mt = 1.0 - t;
+                    let (a, b, c, d) =
+                        (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+                    current.push(Point::new(
+                        a * p0.x + b * c1.x + c * c2.x + d * p.x,
+                        a * p0.y + b * c1.y + c * c2.y + d * p.y,
+                    ));
+                }
+            }
+            PathCmd::Close => {
+                let start = current.first().copied();
+                flush(&mut current, &mut out, true);
+                // Drawing may continue from the start of the closed subpath.
+                if let Some(p) = start {
+                    current.push(p);
+                }
+            }
+        }
+    }
+
+    flush(&mut current, &mut out, false);
+    out
+}
+
+/// Number of line segments needed for a curve, given `n² ≥ error_ratio`.
+fn segment_count(error_ratio: f32) -> usize {
+    (error_ratio.sqrt().ceil() as usize).clamp(1, 256)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_tracks_current_point() {
+        let p = Path::new().move_to(1.0, 2.0).line_to(3.0, 4.0);
+        assert_eq!(p.current_point(), Some(Point::new(3.0, 4.0)));
+        let p = p.close();
+        assert_eq!(p.current_point(), Some(Point::new(1.0, 2.0)));
+    }
+
+    #[test]
+    fn flatten_splits_subpaths() {
+        let p = Path::new()
+            .rect(Rect::new(0.0, 0.0, 10.0, 10.0))
+            .move_to(20.0, 0.0)
+            .line_to(30.0, 0.0);
+        let polys = flatten(&p);
+        assert_eq!(polys.len(), 2);
+        assert!(polys[0].closed);
+        assert_eq!(polys[0].points.len(), 4);
+        assert!(!polys[1].closed);
+    }
+
+    #[test]
+    fn arc_sweep_is_clamped_to_a_full_turn() {
+        let full = Path::new().arc(0.0, 0.0, 10.0, 0.0, TAU);
+        let huge = Path::new().arc(0.0, 0.0, 10.0, 0.0, 1e9);
+        assert_eq!(huge.cmds.len(), full.cmds.len());
+        let inf = Path::new().arc(0.0, 0.0, 10.0, 0.0, f32::INFINITY);
+        assert!(inf.cmds.is_empty());
+    }
+
+    #[test]
+    fn flattened_circle_stays_on_radius() {
+        let polys = flatten(&Path::new().circle(Point::new(50.0, 50.0), 40.0));
+        assert_eq!(polys.len(), 1);
+        for p in &polys[0].points {
+            let r = f32::hypot(p.x - 50.0, p.y - 50.0);
+            assert!((r - 40.0).abs() < 0.1, "r={r}");
+        }
+    }
+
+    #[test]
+    fn arc_to_rounds_corner() {
+        let p = Path::new()
+            .move_to(0.0, 0.0)
+            .arc_to(20.0, 0.0, 20.0, 20.0, 5.0)
+            .line_to(20.0, 20.0);
+        let polys = flatten(&p);
+        // The arc starts at the tangent point (15, 0) and ends at (20, 5).
+        assert!(polys[0].points.contains(&Point::new(15.0, 0.0)));
+        let end = polys[0].points[polys[0].points.len() - 2];
+        assert!((end.x - 20.0).abs() < 1e-3 && (end.y - 5.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bounds_of_rect() {
+        let r = Rect::new(5.0, 6.0, 7.0, 8.0);
+        assert_eq!(Path::new().rect(r).bounds(), r);
+        assert_eq!(Path::new().bounds(), Rect::default());
+    }
+}
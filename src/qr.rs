@@ -0,0 +1,170 @@
+use qrcode::{EcLevel, QrCode, types::Color as Module};
+
+use crate::{canvas::Canvas, color::Color, error::RenderError, geom::Rect};
+
+/// QR error-correction level: how much of the code can be damaged or covered
+/// and still scan, traded against module count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QrEcc {
+    /// About 7% recoverable. Smallest code, so the largest modules.
+    Low,
+    /// About 15% recoverable.
+    #[default]
+    Medium,
+    /// About 25% recoverable.
+    Quartile,
+    /// About 30% recoverable, e.g. with a logo drawn over the middle.
+    High,
+}
+
+impl From<QrEcc> for EcLevel {
+    fn from(ecc: QrEcc) -> Self {
+        match ecc {
+            QrEcc::Low => EcLevel::L,
+            QrEcc::Medium => EcLevel::M,
+            QrEcc::Quartile => EcLevel::Q,
+            QrEcc::High => EcLevel::H,
+        }
+    }
+}
+
+/// Module colors for [`Canvas::draw_qr`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QrColors {
+    pub dark: Color,
+    /// Background behind the code and its quiet zone. Scanners need contrast,
+    /// so keep it light; [`Color::TRANSPARENT`] leaves the canvas showing.
+    pub light: Color,
+}
+
+impl QrColors {
+    pub const fn new(dark: Color, light: Color) -> Self {
+        Self { dark, light }
+    }
+}
+
+impl Default for QrColors {
+    /// Black on white.
+    fn default() -> Self {
+        Self::new(Color::BLACK, Color::WHITE)
+    }
+}
+
+impl Canvas {
+    /// Encode `data` as a QR code and draw it centered in `rect`, surrounded by
+    /// `quiet_zone` modules of `colors.light`.
+    ///
+    /// Modules are whole pixels, as large as fit, so the code stays crisp; any
+    /// leftover space in `rect` is left untouched. A pairing URL at
+    /// [`QrEcc::Medium`] fits a 144 px key with 3–4 px modules.
+    ///
+    /// Fails with [`RenderError::QrEncode`] when `data` is too long for a QR
+    /// code and [`RenderError::QrTooSmall`] when `rect` has less than one pixel
+    /// per module.
+    pub fn draw_qr(
+        &mut self,
+        data: impl AsRef<[u8]>,
+        rect: Rect,
+        ecc: QrEcc,
+        colors: QrColors,
+        quiet_zone: u32,
+    ) -> Result<(), RenderError> {
+        let code = QrCode::with_error_correction_level(data, ecc.into())?;
+        let n = code.width();
+        let side = rect.width.min(rect.height).max(0.0) as u32;
+        // A quiet zone too wide to count can't fit either.
+        let modules = quiet_zone
+            .checked_mul(2)
+            .and_then(|q| q.checked_add(n as u32))
+            .unwrap_or(u32::MAX);
+        let module = side / modules;
+        if module == 0 {
+            return Err(RenderError::QrTooSmall { modules, side });
+        }
+
+        let size = (modules * module) as f32;
+        let x0 = (rect.x + (rect.width - size) / 2.0).round();
+        let y0 = (rect.y + (rect.height - size) / 2.0).round();
+        let m = module as f32;
+
+        self.fill_rect(Rect::new(x0, y0, size, size), colors.light);
+        let dark: Vec<bool> = code
+            .to_colors()
+            .into_iter()
+            .map(|c| c == Module::Dark)
+            .collect();
+        let origin = quiet_zone as f32 * m;
+        for row in 0..n {
+            // One rect per run of dark modules, so there are no seams between them.
+            let mut col = 0;
+            while col < n {
+                if !dark[row * n + col] {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < n && dark[row * n + col] {
+                    col += 1;
+                }
+                self.fill_rect(
+                    Rect::new(
+                        x0 + origin + start as f32 * m,
+                        y0 + origin + row as f32 * m,
+                        (col - start) as f32 * m,
+                        m,
+                    ),
+                    colors.dark,
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modules_are_whole_pixels() {
+        let mut c = Canvas::new(99, 99);
+        c.draw_qr(
+            "https://example.com/pair?code=1234",
+            Rect::new(0.0, 0.0, 99.0, 99.0),
+            QrEcc::Low,
+            QrColors::default(),
+            2,
+        )
+        .unwrap();
+        let img = c.finish().buf;
+        // Every pixel is exactly dark or light.
+        assert!(img.pixels().all(|p| p[0] == 0 || p[0] == 255));
+        // Version 3 is 29 modules + 4 quiet = 33 → 3 px modules. The finder
+        // pattern starts right after the 6 px quiet zone.
+        assert_eq!(img.get_pixel(6, 6).0, [0, 0, 0, 255]);
+        assert_eq!(img.get_pixel(5, 5).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn too_small_rect_is_an_error() {
+        let mut c = Canvas::new(20, 20);
+        let err = c.draw_qr(
+            "hello",
+            Rect::new(0.0, 0.0, 20.0, 20.0),
+            QrEcc::Medium,
+            QrColors::default(),
+            4,
+        );
+        assert!(matches!(err, Err(RenderError::QrTooSmall { .. })));
+        for quiet_zone in [u32::MAX / 2, u32::MAX] {
+            let err = c.draw_qr(
+                "hello",
+                Rect::new(0.0, 0.0, 20.0, 20.0),
+                QrEcc::Medium,
+                QrColors::default(),
+                quiet_zone,
+            );
+            assert!(matches!(err, Err(RenderError::QrTooSmall { .. })));
+        }
+    }
+}
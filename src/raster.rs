@@ -0,0 +1,227 @@
+use crate::{geom::Point, path::FillRule};
+
+/// Vertical sub-scanlines sampled per pixel row. Horizontal coverage is exact.
+const SUBSAMPLES: usize = 16;
+
+/// A rectangular patch of per-pixel coverage values in `[0, 1]`.
+///
+/// `(x, y)` is the canvas position of the top-left cell.
+#[derive(Debug, Clone)]
+pub(crate) struct Coverage {
+    pub x: i32,
+    pub y: i32,
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>,
+}
+
+impl Coverage {
+    /// Coverage at canvas pixel `(px, py)`, or zero outside the patch.
+    #[cfg(test)]
+    pub fn get(&self, px: i32, py: i32) -> f32 {
+        let lx = px - self.x;
+        let ly = py - self.y;
+        if lx < 0 || ly < 0 || lx as usize >= self.width || ly as usize >= self.height {
+            return 0.0;
+        }
+        self.data[ly as usize * self.width + lx as usize]
+    }
+
+    /// Iterate over `(px, py, coverage)` for every cell with non-zero coverage.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32, f32)> + '_ {
+        (0..self.height).flat_map(move |row| {
+            let cells = &self.data[row * self.width..(row + 1) * self.width];
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| **c > 0.0)
+                .map(move |(col, &c)| (self.x + col as i32, self.y + row as i32, c))
+        })
+    }
+}
+
+/// An edge of a polygon, stored with `y0 < y1` and the original direction in `dir`.
+struct Edge {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    dir: i32,
+}
+
+/// Scan-convert closed polygons into an anti-aliased [`Coverage`] patch.
+///
+/// Every polygon is implicitly closed. Only pixels inside `[0, clip_w) × [0, clip_h)`
+/// are produced. Returns `None` when nothing is visible.
+pub(crate) fn rasterize(
+    polygons: &[Vec<Point>],
+    rule: FillRule,
+    clip_w: u32,
+    clip_h: u32,
+) -> Option<Coverage> {
+    let mut edges = Vec::new();
+    let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+    let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+
+    for poly in polygons.iter().filter(|p| p.len() >= 3) {
+        for (i, a) in poly.iter().enumerate() {
+            let b = poly[(i + 1) % poly.len()];
+            min_x = min_x.min(a.x);
+            max_x = max_x.max(a.x);
+            min_y = min_y.min(a.y);
+            max_y = max_y.max(a.y);
+            if a.y == b.y || !(a.y.is_finite() && b.y.is_finite()) {
+                continue;
+            }
+            edges.push(if a.y < b.y {
+                Edge {
+                    x0: a.x,
+                    y0: a.y,
+                    x1: b.x,
+                    y1: b.y,
+                    dir: 1,
+                }
+            } else {
+                Edge {
+                    x0: b.x,
+                    y0: b.y,
+                    x1: a.x,
+                    y1: a.y,
+                    dir: -1,
+                }
+            });
+        }
+    }
+
+    if edges.is_empty() {
+        return None;
+    }
+
+    let x0 = min_x.floor().max(0.0) as i32;
+    let y0 = min_y.floor().max(0.0) as i32;
+    let x1 = (max_x.ceil() as i32).min(clip_w as i32);
+    let y1 = (max_y.ceil() as i32).min(clip_h as i32);
+    if x1 <= x0 || y1 <= y0 {
+        return None;
+    }
+
+    let width = (x1 - x0) as usize;
+    let height = (y1 - y0) as usize;
+    let mut data = vec![0.0_f32; width * height];
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    let weight = 1.0 / SUBSAMPLES as f32;
+
+    for row in 0..height {
+        let out_row = &mut data[row * width..(row + 1) * width];
+        for s in 0..SUBSAMPLES {
+            let sy = (y0 + row as i32) as f32 + (s as f32 + 0.5) / SUBSAMPLES as f32;
+
+            crossings.clear();
+            for e in &edges {
+                if sy >= e.y0 && sy < e.y1 {
+                    let t = (sy - e.y0) / (e.y1 - e.y0);
+                    crossings.push((e.x0 + t * (e.x1 - e.x0), e.dir));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match rule {
+                    FillRule::NonZero => winding != 0,
+                    FillRule::EvenOdd => winding % 2 != 0,
+                };
+                if inside {
+                    add_span(out_row, x0, pair[0].0, pair[1].0, weight);
+                }
+            }
+        }
+    }
+
+    for c in &mut data {
+        *c = c.min(1.0);
+    }
+
+    Some(Coverage {
+        x: x0,
+        y: y0,
+        width,
+        height,
+        data,
+    })
+}
+
+/// Add `weight` × (horizontal overlap) to every cell of `row` covered by `[xa, xb)`.
+fn add_span(row: &mut [f32], row_x: i32, xa: f32, xb: f32, weight: f32) {
+    let left = (xa - row_x as f32).max(0.0);
+    let right = (xb - row_x as f32).min(row.len() as f32);
+    if right <= left {
+        return;
+    }
+
+    let first = left.floor() as usize;
+    let last = (right.ceil() as usize).min(row.len()) - 1;
+    if first == last {
+        row[first] += (right - left) * weight;
+        return;
+    }
+
+    row[first] += (first as f32 + 1.0 - left) * weight;
+    for c in &mut row[first + 1..last] {
+        *c += weight;
+    }
+    row[last] += (right - last as f32) * weight;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, s: f32) -> Vec<Point> {
+        vec![
+            Point::new(x, y),
+            Point::new(x + s, y),
+            Point::new(x + s, y + s),
+            Point::new(x, y + s),
+        ]
+    }
+
+    #[test]
+    fn pixel_aligned_square_is_solid() {
+        let cov = rasterize(&[square(2.0, 2.0, 4.0)], FillRule::NonZero, 10, 10).unwrap();
+        assert_eq!(cov.get(2, 2), 1.0);
+        assert_eq!(cov.get(5, 5), 1.0);
+        assert_eq!(cov.get(6, 6), 0.0);
+        assert_eq!(cov.get(1, 3), 0.0);
+    }
+
+    #[test]
+    fn half_pixel_edge_is_half_covered() {
+        let cov = rasterize(&[square(2.5, 2.0, 4.0)], FillRule::NonZero, 10, 10).unwrap();
+        assert!((cov.get(2, 3) - 0.5).abs() < 1e-5);
+        assert!((cov.get(6, 3) - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn even_odd_punches_hole() {
+        let outer = square(0.0, 0.0, 10.0);
+        let inner = square(3.0, 3.0, 4.0);
+        let polys = [outer, inner];
+
+        let nz = rasterize(&polys, FillRule::NonZero, 10, 10).unwrap();
+        assert_eq!(nz.get(5, 5), 1.0);
+
+        let eo = rasterize(&polys, FillRule::EvenOdd, 10, 10).unwrap();
+        assert_eq!(eo.get(5, 5), 0.0);
+        assert_eq!(eo.get(1, 1), 1.0);
+    }
+
+    #[test]
+    fn clipped_to_canvas() {
+        assert!(rasterize(&[square(20.0, 20.0, 5.0)], FillRule::NonZero, 10, 10).is_none());
+        let cov = rasterize(&[square(-5.0, -5.0, 8.0)], FillRule::NonZero, 10, 10).unwrap();
+        assert_eq!((cov.x, cov.y), (0, 0));
+        assert_eq!(cov.get(2, 2), 1.0);
+    }
+}
@@ -0,0 +1,897 @@
+use std::ops::Range;
+
+use ab_glyph::{Font, PxScale, ScaleFont};
+use unicode_bidi::{Level, ParagraphBidiInfo};
+
+use crate::{
+    canvas::{Canvas, TextOptions, VAlign},
+    color::Color,
+    error::RenderError,
+    font::{FontHandle, FontRegistry},
+    geom::{Point, Rect},
+    layout::{TextDirection, WrapOptions, measure_line},
+    paint::Paint,
+    path::Path,
+    shaping::{Glyph, shape},
+};
+
+/// Size of superscripts and subscripts relative to the surrounding text.
+const SCRIPT_SCALE: f32 = 0.7;
+
+/// Largest font size markup may ask for, in pixels, far beyond any key but
+/// small enough that a typo can't make shaping and rasterizing run away.
+const MAX_SIZE: f32 = 1000.0;
+
+/// Largest relative size markup may ask for, as a factor.
+const MAX_SCALE: f32 = 10.0;
+
+/// How a [`Span`] of rich text looks.
+#[derive(Debug, Clone)]
+pub struct TextStyle {
+    pub font: FontHandle,
+    /// Font size in pixels.
+    pub size: f32,
+    pub color: Paint,
+    /// Pixels the span is raised above the baseline; negative values lower it.
+    pub baseline_shift: f32,
+    pub underline: bool,
+    pub strikethrough: bool,
+}
+
+impl TextStyle {
+    /// White text at `size` pixels on the baseline, without decorations.
+    pub fn new(font: FontHandle, size: f32) -> Self {
+        Self {
+            font,
+            size,
+            color: Paint::Solid(Color::WHITE),
+            baseline_shift: 0.0,
+            underline: false,
+            strikethrough: false,
+        }
+    }
+
+    pub fn font(mut self, font: FontHandle) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn color(mut self, c: impl Into<Paint>) -> Self {
+        self.color = c.into();
+        self
+    }
+
+    pub fn baseline_shift(mut self, px: f32) -> Self {
+        self.baseline_shift = px;
+        self
+    }
+
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    pub fn strikethrough(mut self) -> Self {
+        self.strikethrough = true;
+        self
+    }
+
+    /// Smaller and raised, as for `m²` or `TM`.
+    pub fn superscript(mut self) -> Self {
+        self.baseline_shift += self.size * 0.35;
+        self.size *= SCRIPT_SCALE;
+        self
+    }
+
+    /// Smaller and lowered, as for `CO₂`.
+    pub fn subscript(mut self) -> Self {
+        self.baseline_shift -= self.size * 0.15;
+        self.size *= SCRIPT_SCALE;
+        self
+    }
+
+    /// Ascent, descent and line gap in pixels, with the baseline shift applied.
+    fn metrics(&self) -> (f32, f32, f32) {
+        let sf = self.font.arc().as_scaled(PxScale::from(self.size));
+        (
+            sf.ascent() + self.baseline_shift,
+            sf.descent() + self.baseline_shift,
+            sf.line_gap(),
+        )
+    }
+}
+
+/// A run of text in one style.
+#[derive(Debug, Clone)]
+pub struct Span {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+/// Text made of differently styled spans, e.g. a large bold number followed
+/// by a small unit. Build it span by span or parse it from markup with
+/// [`RichText::parse`], then break it into lines with [`wrap_rich_text`].
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{RichText, TextStyle};
+///
+/// let regular = TextStyle::new(font, 20.0);
+/// let temp = RichText::new()
+///     .span("72", regular.clone().font(bold).size(40.0))
+///     .span("°F", regular);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RichText {
+    pub spans: Vec<Span>,
+}
+
+impl RichText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append `text` in `style`. A `\n` in the text starts a new line.
+    pub fn span(mut self, text: impl Into<String>, style: TextStyle) -> Self {
+        self.spans.push(Span {
+            text: text.into(),
+            style,
+        });
+        self
+    }
+
+    /// Parse markup into spans, starting from `base` and taking fonts by name
+    /// from `fonts`.
+    ///
+    /// Tags nest and are closed with `[/tag]`:
+    ///
+    /// | Tag | Effect |
+    /// |---|---|
+    /// | `[b]`, `[i]` | the fonts registered under the names `"bold"` and `"italic"` |
+    /// | `[font=name]` | a registered font |
+    /// | `[size=40]`, `[size=150%]` | size in pixels (up to 1000) or relative to the enclosing text (up to 1000%) |
+    /// | `[color=#ff8800]` | text color, as `#RRGGBB` or `#RRGGBBAA` |
+    /// | `[sup]`, `[sub]` | superscript, subscript |
+    /// | `[u]`, `[s]` | underline, strikethrough |
+    ///
+    /// `[b]` and `[i]` always look up those two fixed names, so register the
+    /// bold and italic faces as `"bold"` and `"italic"` to use them; markup
+    /// for a registry with other names has to use `[font=name]` instead.
+    ///
+    /// Write `[[` for a literal `[`. Fails with [`RenderError::Markup`] on
+    /// unknown, malformed or unbalanced tags and [`RenderError::FontNotFound`]
+    /// for unregistered fonts, including a `[b]` or `[i]` without a `"bold"`
+    /// or `"italic"` font.
+    ///
+    /// ```rust,ignore
+    /// let label = RichText::parse("[b][size=40]72[/size][/b]°F", &base, &fonts)?;
+    /// let status = RichText::parse("Build [color=#e5484d]failed[/color]", &base, &fonts)?;
+    /// ```
+    pub fn parse(
+        markup: &str,
+        base: &TextStyle,
+        fonts: &FontRegistry,
+    ) -> Result<Self, RenderError> {
+        let mut text = RichText::new();
+        for (tags, s) in parse_markup(markup)? {
+            let style = tags.iter().try_fold(base.clone(), |style, tag| {
+                Ok::<_, RenderError>(match *tag {
+                    Tag::Bold => style.font(fonts.require("bold")?),
+                    Tag::Italic => style.font(fonts.require("italic")?),
+                    Tag::Font(name) => style.font(fonts.require(name)?),
+                    Tag::Size(px) => style.size(px),
+                    Tag::Scale(f) => {
+                        // Nested scales compound, so cap the result too.
+                        let size = (style.size * f).min(MAX_SIZE);
+                        style.size(size)
+                    }
+                    Tag::Color(c) => style.color(c),
+                    Tag::Sup => style.superscript(),
+                    Tag::Sub => style.subscript(),
+                    Tag::Underline => style.underline(),
+                    Tag::Strike => style.strikethrough(),
+                })
+            })?;
+            text = text.span(s, style);
+        }
+        Ok(text)
+    }
+}
+
+/// A markup tag with its argument.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tag<'a> {
+    Bold,
+    Italic,
+    Font(&'a str),
+    Size(f32),
+    Scale(f32),
+    Color(Color),
+    Sup,
+    Sub,
+    Underline,
+    Strike,
+}
+
+/// Split markup into text segments, each with the tags open around it,
+/// outermost first.
+fn parse_markup<'a>(markup: &'a str) -> Result<Vec<(Vec<Tag<'a>>, String)>, RenderError> {
+    let err = |offset: usize, message: String| RenderError::Markup { offset, message };
+    let mut out: Vec<(Vec<Tag<'a>>, String)> = Vec::new();
+    // Open tags with their names, for matching close tags.
+    let mut open: Vec<(&str, Tag<'a>)> = Vec::new();
+    let mut text = String::new();
+    let mut flush = |text: &mut String, open: &[(&'a str, Tag<'a>)]| {
+        if !text.is_empty() {
+            out.push((open.iter().map(|&(_, t)| t).collect(), std::mem::take(text)));
+        }
+    };
+
+    let mut rest = markup;
+    while let Some(i) = rest.find('[') {
+        text.push_str(&rest[..i]);
+        let offset = markup.len() - rest.len() + i;
+        rest = &rest[i + 1..];
+        if let Some(after) = rest.strip_prefix('[') {
+            text.push('[');
+            rest = after;
+            continue;
+        }
+        let Some(end) = rest.find(']') else {
+            return Err(err(offset, "unterminated tag".into()));
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        flush(&mut text, &open);
+
+        if let Some(name) = tag.strip_prefix('/') {
+            match open.pop() {
+                Some((open_name, _)) if open_name == name.trim() => {}
+                Some((open_name, _)) => {
+                    return Err(err(offset, format!("[/{name}] closes [{open_name}]")));
+                }
+                None => return Err(err(offset, format!("[/{name}] has no opening tag"))),
+            }
+            continue;
+        }
+
+        let (name, arg) = match tag.split_once('=') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (tag.trim(), None),
+        };
+        let parsed = match (name, arg) {
+            ("b", None) => Some(Tag::Bold),
+            ("i", None) => Some(Tag::Italic),
+            ("font", Some(font)) if !font.is_empty() => Some(Tag::Font(font)),
+            ("size", Some(size)) => match size.strip_suffix('%') {
+                Some(pct) => pct
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .map(|p| Tag::Scale(p / 100.0)),
+                None => size.parse().ok().map(Tag::Size),
+            }
+            .filter(|t| match *t {
+                Tag::Size(v) => v > 0.0 && v <= MAX_SIZE,
+                Tag::Scale(v) => v > 0.0 && v <= MAX_SCALE,
+                _ => false,
+            }),
+            ("color", Some(hex)) => Color::from_hex(hex).map(Tag::Color),
+            ("sup", None) => Some(Tag::Sup),
+            ("sub", None) => Some(Tag::Sub),
+            ("u", None) => Some(Tag::Underline),
+            ("s", None) => Some(Tag::Strike),
+            _ => None,
+        };
+        match parsed {
+            Some(t) => open.push((name, t)),
+            None => return Err(err(offset, format!("invalid tag [{tag}]"))),
+        }
+    }
+    text.push_str(rest);
+    flush(&mut text, &open);
+
+    match open.last() {
+        Some((name, _)) => Err(err(markup.len(), format!("[{name}] is never closed"))),
+        None => Ok(out),
+    }
+}
+
+/// A single laid-out line of rich text with its pre-computed pixel width.
+#[derive(Debug, Clone)]
+pub struct RichLine {
+    /// Spans in logical order. An empty line holds one empty span, so it still
+    /// has a style to take its height from.
+    pub spans: Vec<Span>,
+    pub width_px: f32,
+}
+
+/// A whitespace-delimited word, possibly spanning several styles.
+struct Word<'a> {
+    /// Span the whitespace before the word belongs to, if any.
+    space: Option<usize>,
+    /// Pieces of the word as `(span index, text)`.
+    pieces: Vec<(usize, &'a str)>,
+}
+
+enum Token<'a> {
+    Word(Word<'a>),
+    /// A `\n` in span `i`.
+    Break(usize),
+}
+
+/// Split the spans into words and hard line breaks.
+fn tokens(text: &RichText) -> Vec<Token<'_>> {
+    let mut out = Vec::new();
+    let mut space = None;
+    let mut word: Option<Word<'_>> = None;
+    for (i, span) in text.spans.iter().enumerate() {
+        let mut start = None;
+        for (j, ch) in span.text.char_indices().chain([(span.text.len(), ' ')]) {
+            let at_end = j == span.text.len();
+            if !at_end && !ch.is_whitespace() {
+                start.get_or_insert(j);
+                continue;
+            }
+            if let Some(s) = start.take() {
+                word.get_or_insert_with(|| Word {
+                    space: space.take(),
+                    pieces: Vec::new(),
+                })
+                .pieces
+                .push((i, &span.text[s..j]));
+            }
+            if at_end {
+                break;
+            }
+            out.extend(word.take().map(Token::Word));
+            if ch == '\n' {
+                out.push(Token::Break(i));
+                space = None;
+            } else {
+                space.get_or_insert(i);
+            }
+        }
+    }
+    out.extend(word.map(Token::Word));
+    out
+}
+
+/// A line being filled by [`wrap_rich_text`].
+struct LineBuilder<'a> {
+    text: &'a RichText,
+    spans: Vec<Span>,
+    /// Source span of the last entry in `spans`, to merge pieces into.
+    last: Option<usize>,
+    width: f32,
+}
+
+impl<'a> LineBuilder<'a> {
+    fn new(text: &'a RichText) -> Self {
+        Self {
+            text,
+            spans: Vec::new(),
+            last: None,
+            width: 0.0,
+        }
+    }
+
+    fn push(&mut self, i: usize, s: &str) {
+        match self.spans.last_mut() {
+            Some(span) if self.last == Some(i) => span.text.push_str(s),
+            _ => self.spans.push(Span {
+                text: s.to_string(),
+                style: self.text.spans[i].style.clone(),
+            }),
+        }
+        self.last = Some(i);
+    }
+
+    /// Finish the line. `style` is the span an empty line takes its height from.
+    fn finish(&mut self, style: usize) -> RichLine {
+        if self.spans.is_empty() {
+            self.push(style, "");
+        }
+        self.last = None;
+        RichLine {
+            spans: std::mem::take(&mut self.spans),
+            width_px: std::mem::take(&mut self.width),
+        }
+    }
+}
+
+/// Greedy word-wrap for rich text, like [`crate::wrap_text`]: words are
+/// split on whitespace, may change style mid-word, and are measured in their
+/// own fonts and sizes. A `\n` in a span forces a line break.
+///
+/// Returns at most `opts.max_lines` lines; anything beyond is appended to the
+/// final line.
+pub fn wrap_rich_text(text: &RichText, opts: &WrapOptions) -> Vec<RichLine> {
+    if opts.max_lines == 0 {
+        return vec![];
+    }
+    let measure = |i: usize, s: &str| {
+        let style = &text.spans[i].style;
+        measure_line(&style.font, style.size, s)
+    };
+
+    let mut lines = Vec::new();
+    let mut line = LineBuilder::new(text);
+    let mut last_style = 0;
+    for token in tokens(text) {
+        let word = match token {
+            Token::Break(i) if lines.len() + 1 < opts.max_lines => {
+                lines.push(line.finish(last_style));
+                last_style = i;
+                continue;
+            }
+            // On the last allowed line a break is just a space.
+            Token::Break(i) => Word {
+                space: Some(i),
+                pieces: Vec::new(),
+            },
+            Token::Word(word) => word,
+        };
+
+        let word_w: f32 = word.pieces.iter().map(|&(i, s)| measure(i, s)).sum();
+        if let (false, Some(sp)) = (line.spans.is_empty(), word.space) {
+            let space_w = measure(sp, " ");
+            let last_line = lines.len() + 1 >= opts.max_lines;
+            if !last_line && line.width + space_w + word_w > opts.max_width {
+                lines.push(line.finish(last_style));
+            } else {
+                line.push(sp, " ");
+                line.width += space_w;
+            }
+        }
+        for &(i, s) in &word.pieces {
+            line.push(i, s);
+            last_style = i;
+        }
+        line.width += word_w;
+    }
+    if !line.spans.is_empty() || !lines.is_empty() {
+        lines.push(line.finish(last_style));
+    }
+    lines
+}
+
+/// Split the texts of a line's spans into pieces in visual order, left to
+/// right.
+///
+/// The Unicode Bidirectional Algorithm runs over the whole line, so a run
+/// that crosses span boundaries (`72` in bold, then `°F`) is ordered as one.
+/// Each piece is `(span index, byte range in its text, direction of its run)`.
+fn visual_pieces(
+    texts: &[&str],
+    direction: TextDirection,
+) -> Vec<(usize, Range<usize>, TextDirection)> {
+    let text = texts.concat();
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let level = match direction {
+        TextDirection::Ltr => Level::ltr(),
+        TextDirection::Rtl => Level::rtl(),
+    };
+    let bidi = ParagraphBidiInfo::new(&text, Some(level));
+    let (levels, runs) = bidi.visual_runs(0..text.len());
+
+    let mut pieces = Vec::new();
+    for run in runs {
+        let dir = match levels[run.start].is_rtl() {
+            true => TextDirection::Rtl,
+            false => TextDirection::Ltr,
+        };
+        let mut start = 0;
+        let mut in_run = Vec::new();
+        for (i, t) in texts.iter().enumerate() {
+            let end = start + t.len();
+            let (from, to) = (run.start.max(start), run.end.min(end));
+            if from < to {
+                in_run.push((i, from - start..to - start, dir));
+            }
+            start = end;
+        }
+        // Within a right-to-left run the later spans sit further left.
+        if dir == TextDirection::Rtl {
+            in_run.reverse();
+        }
+        pieces.extend(in_run);
+    }
+    pieces
+}
+
+/// A piece of a span positioned on the canvas: its baseline origin, shift
+/// applied.
+struct PlacedSpan<'a> {
+    span: &'a Span,
+    text: &'a str,
+    direction: TextDirection,
+    origin: Point,
+    width: f32,
+}
+
+impl Canvas {
+    /// Render pre-wrapped rich text onto the canvas.
+    ///
+    /// Fonts, sizes and colors come from the spans; `opts` supplies the
+    /// alignment, line gap, padding, direction, effects and outline.
+    pub fn draw_rich_text(
+        &mut self,
+        lines: &[RichLine],
+        opts: &TextOptions,
+    ) -> Result<(), RenderError> {
+        let full = Rect::new(0.0, 0.0, self.width() as f32, self.height() as f32);
+        self.draw_rich_text_in(full, lines, opts).map(|_| ())
+    }
+
+    /// Render rich text aligned within `rect`, inset by `opts.padding`.
+    ///
+    /// Spans on a line share a baseline, and each line is as tall as its
+    /// tallest span, so a large number and a small unit sit on the same
+    /// baseline. Each line is ordered with the Unicode Bidirectional
+    /// Algorithm across its spans, so mixed-direction text reads correctly
+    /// whichever spans its runs fall in.
+    ///
+    /// Returns the rectangle the text occupies, as [`Canvas::draw_text_in`].
+    pub fn draw_rich_text_in(
+        &mut self,
+        rect: Rect,
+        lines: &[RichLine],
+        opts: &TextOptions,
+    ) -> Result<Rect, RenderError> {
+        let area = rect.inset_by(opts.padding);
+        if lines.is_empty() {
+            return Ok(Rect::new(area.x, area.y, 0.0, 0.0));
+        }
+        if let Some(mode) = opts.blend_mode {
+            return self.with_blend_mode(mode, |c| {
+                let opts = TextOptions {
+                    blend_mode: None,
+                    padding: Default::default(),
+                    ..opts.clone()
+                };
+                c.draw_rich_text_in(area, lines, &opts)
+            });
+        }
+        if !opts.effects.is_empty() {
+            return self.with_effects(&opts.effects, |c| {
+                let opts = TextOptions {
+                    effects: Vec::new(),
+                    padding: Default::default(),
+                    ..opts.clone()
+                };
+                c.draw_rich_text_in(area, lines, &opts)
+            });
+        }
+
+        let direction = opts
+            .direction
+            .or_else(|| {
+                lines
+                    .iter()
+                    .flat_map(|l| &l.spans)
+                    .find_map(|s| TextDirection::detect(&s.text))
+            })
+            .unwrap_or_default();
+        let align = opts.h_align.fraction(direction);
+
+        // Ascent, descent and line gap of each line. A line without spans
+        // has no style to take a height from, so it takes none.
+        let metrics: Vec<(f32, f32, f32)> = lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|s| s.style.metrics())
+                    .reduce(|(a, d, g), (sa, sd, sg)| (a.max(sa), d.min(sd), g.max(sg)))
+                    .unwrap_or_default()
+            })
+            .collect();
+        let total_h: f32 = metrics.iter().map(|(a, d, _)| a - d).sum::<f32>()
+            + metrics[..metrics.len() - 1]
+                .iter()
+                .map(|(_, _, g)| g + opts.line_gap)
+                .sum::<f32>();
+        let top = match opts.v_align {
+            VAlign::Top => area.y,
+            VAlign::Center => area.y + (area.height - total_h) / 2.0,
+            VAlign::Bottom => area.bottom() - total_h,
+            VAlign::Baseline(y) => y - metrics[0].0,
+        };
+
+        let mut placed = Vec::new();
+        let mut bounds: Option<Rect> = None;
+        let mut y = top;
+        for (line, &(ascent, descent, gap)) in lines.iter().zip(&metrics) {
+            let baseline = y + ascent;
+            let mut x = area.x + (area.width - line.width_px) * align;
+            let line_box = Rect::new(x, y, line.width_px, ascent - descent);
+            bounds = Some(bounds.map_or(line_box, |b| b.union(line_box)));
+
+            let texts: Vec<&str> = line.spans.iter().map(|s| s.text.as_str()).collect();
+            for (i, range, dir) in visual_pieces(&texts, direction) {
+                let span = &line.spans[i];
+                let text = &span.text[range];
+                let width = shape(&span.style.font, span.style.size, text, Some(dir)).advance;
+                placed.push(PlacedSpan {
+                    span,
+                    text,
+                    direction: dir,
+                    origin: Point::new(x, baseline - span.style.baseline_shift),
+                    width,
+                });
+                x += width;
+            }
+            y += ascent - descent + gap + opts.line_gap;
+        }
+
+        // Stroke every span before filling any, so outlines never cover glyphs.
+        if let Some(stroke) = &opts.stroke {
+            let outline = placed
+                .iter()
+                .flat_map(|p| {
+                    let style = &p.span.style;
+                    shape(&style.font, style.size, p.text, Some(p.direction))
+                        .placed(p.origin.x, p.origin.y)
+                        .filter_map(|(glyph, origin)| match glyph {
+                            Glyph::Font { face, id } => {
+                                style.font.glyph_path(face, id, style.size, origin)
+                            }
+                            Glyph::Image(_) => None,
+                        })
+                })
+                .fold(Path::new(), Path::append);
+            self.stroke_path(&outline, stroke, opts.stroke_color.clone());
+        }
+
+        if opts.fill {
+            for p in &placed {
+                let style = &p.span.style;
+                self.draw_text_line(
+                    p.text,
+                    p.direction,
+                    &style.font,
+                    style.size,
+                    p.origin,
+                    &style.color,
+                );
+                for (on, strikeout) in [(style.underline, false), (style.strikethrough, true)] {
+                    if on {
+                        let (offset, thickness) = style.font.decoration(style.size, strikeout);
+                        let y = p.origin.y + offset - thickness / 2.0;
+                        let line = Rect::new(p.origin.x, y, p.width, thickness);
+                        self.fill_rect(line, style.color.clone());
+                    }
+                }
+            }
+        }
+
+        let outset = opts.stroke.as_ref().map_or(0.0, |s| s.width / 2.0);
+        Ok(bounds.unwrap_or_default().inset(-outset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markup_nests_tags_around_segments() {
+        let parsed =
+            parse_markup("[b][size=40]72[/size][/b]°F [color=#ff0000]hot[/color]").unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                (vec![Tag::Bold, Tag::Size(40.0)], "72".to_string()),
+                (vec![], "°F ".to_string()),
+                (vec![Tag::Color(Color::rgb(255, 0, 0))], "hot".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_markup("m[sup]2[/sup] [[x]").unwrap(),
+            vec![
+                (vec![], "m".to_string()),
+                (vec![Tag::Sup], "2".to_string()),
+                (vec![], " [x]".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_markup("[size=50%]a[/size]").unwrap(),
+            vec![(vec![Tag::Scale(0.5)], "a".to_string())]
+        );
+    }
+
+    #[test]
+    fn rtl_line_orders_bidi_runs_across_spans() {
+        use TextDirection::{Ltr, Rtl};
+        // "שלום [b]72[/b]°F": the number and unit read left to right as one
+        // run, to the left of the Hebrew word.
+        let texts = ["שלום ", "72", "°F"];
+        assert_eq!(
+            visual_pieces(&texts, Rtl),
+            [(1, 0..2, Ltr), (2, 0..3, Ltr), (0, 0..9, Rtl)]
+        );
+        let texts = ["a ", "שלום", " b"];
+        assert_eq!(
+            visual_pieces(&texts, Ltr),
+            [(0, 0..2, Ltr), (1, 0..8, Rtl), (2, 0..2, Ltr)]
+        );
+        // A right-to-left run split across spans keeps its later span leftmost.
+        let texts = ["של", "ום"];
+        assert_eq!(visual_pieces(&texts, Rtl), [(1, 0..4, Rtl), (0, 0..4, Rtl)]);
+    }
+
+    #[test]
+    fn markup_rejects_bad_tags() {
+        for bad in [
+            "[b]unclosed",
+            "[b]x[/i]",
+            "x[/b]",
+            "[blink]x[/blink]",
+            "[size=big]x[/size]",
+            "[size=0]x[/size]",
+            "[size=inf]x[/size]",
+            "[size=1e30]x[/size]",
+            "[size=NaN]x[/size]",
+            "[size=inf%]x[/size]",
+            "[color=red]x[/color]",
+            "[b",
+        ] {
+            assert!(
+                matches!(parse_markup(bad), Err(RenderError::Markup { .. })),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn bold_and_italic_tags_need_fonts_with_those_names() {
+        let mut fonts = FontRegistry::new();
+        let sans = fonts
+            .load_bytes("sans", crate::test_fonts::DEJAVU_SANS)
+            .unwrap();
+        let base = TextStyle::new(sans, 20.0);
+        for markup in ["[b]x[/b]", "[i]x[/i]"] {
+            let err = RichText::parse(markup, &base, &fonts).unwrap_err();
+            assert!(
+                matches!(err, RenderError::FontNotFound(ref n) if n == "bold" || n == "italic")
+            );
+        }
+        fonts
+            .load_bytes("bold", crate::test_fonts::DEJAVU_SANS)
+            .unwrap();
+        assert!(RichText::parse("[b]x[/b]", &base, &fonts).is_ok());
+        assert!(RichText::parse("[font=sans]x[/font]", &base, &fonts).is_ok());
+    }
+
+    #[test]
+    fn line_without_spans_takes_no_height() {
+        let font = crate::test_fonts::dejavu();
+        let text = RichText::new().span("x", TextStyle::new(font.clone(), 20.0));
+        let mut lines = wrap_rich_text(&text, &WrapOptions::default());
+        let one = Canvas::new(60, 60)
+            .draw_rich_text_in(
+                Rect::new(0.0, 0.0, 60.0, 60.0),
+                &lines,
+                &TextOptions::new(font.clone(), 20.0),
+            )
+            .unwrap();
+        lines.insert(
+            0,
+            RichLine {
+                spans: Vec::new(),
+                width_px: 0.0,
+            },
+        );
+        let mut c = Canvas::new(60, 60);
+        let opts = TextOptions::new(font, 20.0).v_align(VAlign::Top);
+        let two = c
+            .draw_rich_text_in(Rect::new(0.0, 0.0, 60.0, 60.0), &lines, &opts)
+            .unwrap();
+        assert!(two.y.is_finite() && two.height.is_finite());
+        assert_eq!(two.height, one.height);
+        assert!(c.finish().buf.pixels().any(|p| p[3] > 0));
+    }
+
+    fn line_text(line: &RichLine) -> String {
+        line.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn wrap_breaks_across_spans_and_measures_each_style() {
+        let font = crate::test_fonts::dejavu();
+        let small = TextStyle::new(font.clone(), 20.0);
+        let big = TextStyle::new(font.clone(), 30.0);
+        // `world` changes style mid-word and must stay whole.
+        let text = RichText::new()
+            .span("hello wor", small.clone())
+            .span("ld again", big.clone());
+        let m = |style: &TextStyle, s: &str| measure_line(&style.font, style.size, s);
+        let first_w = m(&small, "hello wor") + m(&big, "ld");
+        let opts = WrapOptions {
+            max_width: first_w + m(&big, " again") - 1.0,
+            max_lines: 3,
+        };
+        let lines = wrap_rich_text(&text, &opts);
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(line_text(&lines[0]), "hello world");
+        assert_eq!(
+            lines[0]
+                .spans
+                .iter()
+                .map(|s| s.style.size)
+                .collect::<Vec<_>>(),
+            vec![20.0, 30.0]
+        );
+        assert!((lines[0].width_px - first_w).abs() < 1e-3);
+        assert_eq!(line_text(&lines[1]), "again");
+        assert!((lines[1].width_px - m(&big, "again")).abs() < 1e-3);
+    }
+
+    #[test]
+    fn breaks_fold_into_spaces_on_the_last_line() {
+        let style = TextStyle::new(crate::test_fonts::dejavu(), 20.0);
+        let text = RichText::new().span("a\nb\nc", style);
+        let opts = WrapOptions {
+            max_width: 1000.0,
+            max_lines: 2,
+        };
+        let lines: Vec<_> = wrap_rich_text(&text, &opts).iter().map(line_text).collect();
+        assert_eq!(lines, vec!["a", "b c"]);
+    }
+
+    #[test]
+    fn empty_lines_keep_their_style() {
+        let font = crate::test_fonts::dejavu();
+        let text = RichText::new()
+            .span("top\n", TextStyle::new(font.clone(), 40.0))
+            .span("\nbottom", TextStyle::new(font, 10.0));
+        let lines = wrap_rich_text(&text, &WrapOptions::default());
+        assert_eq!(
+            lines.iter().map(line_text).collect::<Vec<_>>(),
+            vec!["top", "", "bottom"]
+        );
+        assert_eq!(lines[1].spans.len(), 1);
+        assert_eq!(lines[1].spans[0].style.size, 40.0);
+        assert_eq!(lines[1].width_px, 0.0);
+    }
+
+    #[test]
+    fn spans_of_different_sizes_share_a_baseline() {
+        let font = crate::test_fonts::dejavu();
+        let text = RichText::new()
+            .span("X", TextStyle::new(font.clone(), 48.0))
+            .span("x", TextStyle::new(font.clone(), 14.0));
+        let lines = wrap_rich_text(&text, &WrapOptions::default());
+        let mut c = Canvas::new(80, 80);
+        let opts = TextOptions::new(font.clone(), 20.0).h_align(crate::HAlign::Left);
+        c.draw_rich_text(&lines, &opts).unwrap();
+        let img = c.finish().buf;
+
+        // Lowest inked row in columns `xs`: both glyphs stand on the baseline.
+        let bottom = |xs: std::ops::Range<u32>| {
+            (0..80)
+                .rev()
+                .find(|&y| xs.clone().any(|x| img.get_pixel(x, y)[3] > 128))
+                .unwrap()
+        };
+        let split = measure_line(&font, 48.0, "X").round() as u32;
+        let (big, small) = (bottom(0..split), bottom(split..80));
+        assert!(big.abs_diff(small) <= 1, "{big} vs {small}");
+        // The small glyph is much shorter than the big one.
+        let top = |xs: std::ops::Range<u32>| {
+            (0..80)
+                .find(|&y| xs.clone().any(|x| img.get_pixel(x, y)[3] > 128))
+                .unwrap()
+        };
+        assert!(top(split..80) > top(0..split) + 15);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    border::{rrect_sdf, smoothstep},
+    geom::{CornerRadii, Point, Rect},
+};
+
+/// How the ends of an open line are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineCap {
+    /// The line stops exactly at its endpoints.
+    #[default]
+    Butt,
+    /// A half-circle of diameter `thickness` is added at each end.
+    Round,
+    /// The line is extended by half its thickness past each endpoint.
+    Square,
+}
+
+/// Map a signed distance (negative inside) to pixel coverage in `[0, 1]`.
+///
+/// The edge is anti-aliased over one pixel centered on the shape boundary, so
+/// pixel-aligned rectangles stay crisp.
+#[inline]
+pub(crate) fn sdf_coverage(d: f32) -> f32 {
+    smoothstep(0.5, -0.5, d)
+}
+
+/// Rounded-rect SDF with an individual radius per corner.
+///
+/// Radii are clamped to half the shorter side so opposing corners never overlap.
+pub(crate) fn rrect_radii_sdf(px: f32, py: f32, rect: &Rect, radii: &CornerRadii) -> f32 {
+    let c = rect.center();
+    let hw = rect.width / 2.0;
+    let hh = rect.height / 2.0;
+
+    // Canvas Y grows downward, so "top" is the half with py < cy.
+    let r = match (px >= c.x, py >= c.y) {
+        (false, false) => radii.top_left,
+        (true, false) => radii.top_right,
+        (true, true) => radii.bottom_right,
+        (false, true) => radii.bottom_left,
+    };
+    let r = r.clamp(0.0, hw.min(hh));
+
+    rrect_sdf(px, py, c.x, c.y, hw, hh, r)
+}
+
+/// Approximate SDF of an axis-aligned ellipse centered at `(cx, cy)` with radii `(rx, ry)`.
+///
+/// Exact near the boundary, which is all the anti-aliasing needs.
+pub(crate) fn ellipse_sdf(px: f32, py: f32, cx: f32, cy: f32, rx: f32, ry: f32) -> f32 {
+    let x = px - cx;
+    let y = py - cy;
+    let k0 = f32::hypot(x / rx, y / ry);
+    let k1 = f32::hypot(x / (rx * rx), y / (ry * ry));
+    if k1 == 0.0 {
+        // Exactly at the centre: distance to the nearest vertex.
+        return -rx.min(ry);
+    }
+    k0 * (k0 - 1.0) / k1
+}
+
+/// SDF of a thick line segment from `a` to `b` with the given cap style.
+pub(crate) fn segment_sdf(px: f32, py: f32, a: Point, b: Point, half_t: f32, cap: LineCap) -> f32 {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len = f32::hypot(dx, dy);
+
+    if len == 0.0 {
+        // Degenerate line: only round and square caps produce visible output.
+        return match cap {
+            LineCap::Butt => f32::INFINITY,
+            LineCap::Round => f32::hypot(px - a.x, py - a.y) - half_t,
+            LineCap::Square => (px - a.x).abs().max((py - a.y).abs()) - half_t,
+        };
+    }
+
+    // Project into segment-local coordinates: `u` along the line from its
+    // midpoint, `v` across it.
+    let (ux, uy) = (dx / len, dy / len);
+    let mx = px - (a.x + b.x) / 2.0;
+    let my = py - (a.y + b.y) / 2.0;
+    let u = (mx * ux + my * uy).abs();
+    let v = (-mx * uy + my * ux).abs();
+    let half_len = len / 2.0;
+
+    match cap {
+        LineCap::Round => f32::hypot((u - half_len).max(0.0), v) - half_t,
+        LineCap::Butt => rrect_sdf(u, v, 0.0, 0.0, half_len, half_t, 0.0),
+        LineCap::Square => rrect_sdf(u, v, 0.0, 0.0, half_len + half_t, half_t, 0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coverage_is_half_on_edge() {
+        assert!((sdf_coverage(0.0) - 0.5).abs() < 1e-6);
+        assert_eq!(sdf_coverage(-1.0), 1.0);
+        assert_eq!(sdf_coverage(1.0), 0.0);
+    }
+
+    #[test]
+    fn per_corner_radii_pick_quadrant() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let radii = CornerRadii::new(20.0, 0.0, 0.0, 0.0);
+        // Near the rounded top-left corner the corner pixel lies outside …
+        assert!(rrect_radii_sdf(1.0, 1.0, &rect, &radii) > 0.0);
+        // … while the square top-right corner pixel is still inside.
+        assert!(rrect_radii_sdf(99.0, 1.0, &rect, &radii) < 0.0);
+    }
+
+    #[test]
+    fn ellipse_boundary_near_zero() {
+        let d = ellipse_sdf(30.0, 10.0, 10.0, 10.0, 20.0, 10.0);
+        assert!(d.abs() < 1e-3, "d={d}");
+        assert!(ellipse_sdf(10.0, 10.0, 10.0, 10.0, 20.0, 10.0) < 0.0);
+    }
+
+    #[test]
+    fn segment_caps() {
+        let a = Point::new(10.0, 10.0);
+        let b = Point::new(20.0, 10.0);
+        // Two pixels beyond the end: only a square cap (extension 3) covers it.
+        assert!(segment_sdf(22.0, 10.0, a, b, 3.0, LineCap::Butt) > 0.0);
+        assert!(segment_sdf(22.0, 10.0, a, b, 3.0, LineCap::Square) < 0.0);
+        assert!(segment_sdf(22.0, 10.0, a, b, 3.0, LineCap::Round) < 0.0);
+        // Diagonally off the end the round cap falls away before the square one.
+        assert!(segment_sdf(22.5, 12.5, a, b, 3.0, LineCap::Round) > 0.0);
+        assert!(segment_sdf(22.5, 12.5, a, b, 3.0, LineCap::Square) < 0.0);
+    }
+}
@@ -0,0 +1,375 @@
+use std::{ops::Range, sync::Arc};
+
+use ab_glyph::{Font, GlyphId, ScaleFont};
+use image::RgbaImage;
+use rustybuzz::{Direction, Feature, UnicodeBuffer, ttf_parser::Tag};
+use unicode_bidi::{BidiClass, Level, ParagraphBidiInfo, bidi_class};
+
+use crate::{emoji, font::FontHandle, geom::Point, layout::TextDirection};
+
+/// What a shaped glyph is drawn from.
+#[derive(Debug, Clone)]
+pub(crate) enum Glyph {
+    /// Glyph `id` of face `face` of the font's fallback chain (0 = primary).
+    Font { face: usize, id: GlyphId },
+    /// An emoji picture from the font's emoji directory, one em square.
+    Image(Arc<RgbaImage>),
+}
+
+/// A glyph placed by the shaper.
+#[derive(Debug, Clone)]
+pub(crate) struct ShapedGlyph {
+    pub glyph: Glyph,
+    /// Glyph origin relative to the start of the run's baseline, y down.
+    /// Includes kerning and mark offsets.
+    pub offset: Point,
+}
+
+/// One line of text shaped into positioned glyphs, in visual order.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GlyphRun {
+    pub glyphs: Vec<ShapedGlyph>,
+    /// Pen advance after the last glyph: the line's width.
+    pub advance: f32,
+}
+
+impl GlyphRun {
+    /// Glyphs with their origins for a run starting at `(x, baseline_y)`.
+    pub(crate) fn placed(self, x: f32, baseline_y: f32) -> impl Iterator<Item = (Glyph, Point)> {
+        self.glyphs.into_iter().map(move |g| {
+            let origin = Point::new(x + g.offset.x, baseline_y + g.offset.y);
+            (g.glyph, origin)
+        })
+    }
+
+    /// Continue this run with `run`, placed after its last glyph.
+    fn append(&mut self, run: GlyphRun) {
+        let x = self.advance;
+        self.glyphs
+            .extend(run.glyphs.into_iter().map(|g| ShapedGlyph {
+                offset: Point::new(x + g.offset.x, g.offset.y),
+                ..g
+            }));
+        self.advance += run.advance;
+    }
+}
+
+/// Shape `text` in `font` at `size` pixels with the handle's OpenType features.
+///
+/// Ligatures, contextual forms, GPOS kerning and mark positioning all come from
+/// the font. Mixed-direction text is split into runs with the Unicode
+/// Bidirectional Algorithm, taking `direction` as the paragraph direction or
+/// detecting it when `None`, and the runs are laid out in visual order.
+/// Characters the primary font lacks are shaped in the first fallback that
+/// has them, and emoji come from the emoji directory or a color font when
+/// there is one.
+pub(crate) fn shape(
+    font: &FontHandle,
+    size: f32,
+    text: &str,
+    direction: Option<TextDirection>,
+) -> GlyphRun {
+    let level = direction.map(|d| match d {
+        TextDirection::Ltr => Level::ltr(),
+        TextDirection::Rtl => Level::rtl(),
+    });
+    let bidi = ParagraphBidiInfo::new(text, level);
+    let runs = if bidi.has_rtl() {
+        let (levels, runs) = bidi.visual_runs(0..text.len());
+        runs.into_iter()
+            .map(|r| (levels[r.start].is_rtl(), r))
+            .collect()
+    } else {
+        vec![(false, 0..text.len())]
+    };
+
+    let mut line = GlyphRun::default();
+    for (rtl, range) in runs {
+        let offset = range.start;
+        let mut pieces = source_runs(font, &text[range]);
+        if rtl {
+            pieces.reverse();
+        }
+        for (source, r) in pieces {
+            let r = offset + r.start..offset + r.end;
+            line.append(match source {
+                Source::Face(face) => shape_run(font, face, size, &text[r], rtl),
+                Source::Image(img) => image_run(font, size, img),
+            });
+        }
+    }
+    line
+}
+
+/// Where a run of text is drawn from.
+enum Source {
+    Face(usize),
+    Image(Arc<RgbaImage>),
+}
+
+/// Split `text` into runs of characters taken from the same face, and emoji
+/// drawn as images. Marks and joiners stay with the character before them,
+/// and emoji sequences stay whole, so clusters aren't broken up.
+fn source_runs(font: &FontHandle, text: &str) -> Vec<(Source, Range<usize>)> {
+    let mut runs: Vec<(Source, Range<usize>)> = Vec::new();
+    let mut i = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        let (face, end) = match emoji::sequence_len(&text[i..]) {
+            Some(n) => {
+                let seq = &text[i..i + n];
+                if let Some(img) = font.emoji().and_then(|d| d.lookup(seq)) {
+                    runs.push((Source::Image(img), i..i + n));
+                    i += n;
+                    continue;
+                }
+                (font.emoji_face_for(ch), i + n)
+            }
+            None => {
+                let joins = matches!(bidi_class(ch), BidiClass::NSM | BidiClass::BN);
+                match runs.last_mut() {
+                    Some((Source::Face(_), r)) if joins => {
+                        r.end = i + ch.len_utf8();
+                        i = r.end;
+                        continue;
+                    }
+                    _ => (font.face_for(ch), i + ch.len_utf8()),
+                }
+            }
+        };
+        match runs.last_mut() {
+            Some((Source::Face(f), r)) if *f == face => r.end = end,
+            _ => runs.push((Source::Face(face), i..end)),
+        }
+        i = end;
+    }
+    runs
+}
+
+/// A single emoji image, one em wide.
+fn image_run(font: &FontHandle, size: f32, img: Arc<RgbaImage>) -> GlyphRun {
+    GlyphRun {
+        glyphs: vec![ShapedGlyph {
+            glyph: Glyph::Image(img),
+            offset: Point::new(0.0, 0.0),
+        }],
+        advance: emoji::image_box(font, size, Point::new(0.0, 0.0)).width,
+    }
+}
+
+/// Shape a single-direction run in face `face`. Right-to-left runs come back
+/// in visual order.
+fn shape_run(font: &FontHandle, face: usize, size: f32, text: &str, rtl: bool) -> GlyphRun {
+    let Some(hb_face) = font.tables(face) else {
+        return shape_nominal(font, face, size, text, rtl);
+    };
+    let features: Vec<Feature> = font
+        .features()
+        .iter()
+        .map(|f| Feature::new(Tag::from_bytes(&f.tag), f.value, ..))
+        .collect();
+
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.set_direction(if rtl {
+        Direction::RightToLeft
+    } else {
+        Direction::LeftToRight
+    });
+    let shaped = rustybuzz::shape(hb_face, &features, buffer);
+
+    // Shaper units are font units, y up; scale them the way ab_glyph does.
+    let sf = font.face(face).as_scaled(font.face_scale(face, size));
+    let (hs, vs) = (sf.h_scale_factor(), sf.v_scale_factor());
+    let mut pen = Point::new(0.0, 0.0);
+    let glyphs = shaped
+        .glyph_infos()
+        .iter()
+        .zip(shaped.glyph_positions())
+        .map(|(info, pos)| {
+            let glyph = ShapedGlyph {
+                glyph: Glyph::Font {
+                    face,
+                    id: GlyphId(info.glyph_id as u16),
+                },
+                offset: Point::new(
+                    pen.x + pos.x_offset as f32 * hs,
+                    pen.y - pos.y_offset as f32 * vs,
+                ),
+            };
+            pen.x += pos.x_advance as f32 * hs;
+            pen.y -= pos.y_advance as f32 * vs;
+            glyph
+        })
+        .collect();
+    GlyphRun {
+        glyphs,
+        advance: pen.x,
+    }
+}
+
+/// One glyph per `char` with legacy `kern` pairs, for fonts the shaper can't
+/// parse.
+fn shape_nominal(font: &FontHandle, face: usize, size: f32, text: &str, rtl: bool) -> GlyphRun {
+    let sf = font.face(face).as_scaled(font.face_scale(face, size));
+    let mut chars: Vec<char> = text.chars().collect();
+    if rtl {
+        chars.reverse();
+    }
+    let mut run = GlyphRun::default();
+    let mut prev = None;
+    for ch in chars {
+        let id = sf.glyph_id(ch);
+        if let Some(prev) = prev {
+            run.advance += sf.kern(prev, id);
+        }
+        run.glyphs.push(ShapedGlyph {
+            glyph: Glyph::Font { face, id },
+            offset: Point::new(run.advance, 0.0),
+        });
+        run.advance += sf.h_advance(id);
+        prev = Some(id);
+    }
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{font::FontFeature, layout::measure_line, test_fonts};
+
+    fn ids(run: &GlyphRun) -> Vec<u16> {
+        run.glyphs
+            .iter()
+            .map(|g| match g.glyph {
+                Glyph::Font { id, .. } => id.0,
+                Glyph::Image(_) => u16::MAX,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn measure_line_is_the_shaped_advance() {
+        let font = test_fonts::dejavu();
+        let run = shape(&font, 20.0, "Hello, World", None);
+        assert_eq!(run.glyphs.len(), 12);
+        assert_eq!(measure_line(&font, 20.0, "Hello, World"), run.advance);
+        // Each glyph sits where the previous one's advance left the pen.
+        let last = run.glyphs.last().unwrap().offset.x;
+        let sf = font.arc().as_scaled(font.face_scale(0, 20.0));
+        assert!((last + sf.h_advance(sf.glyph_id('d')) - run.advance).abs() < 1e-3);
+    }
+
+    #[test]
+    fn liga_toggle_changes_glyph_count() {
+        let font = test_fonts::dejavu();
+        assert_eq!(shape(&font, 20.0, "fi", None).glyphs.len(), 1);
+        let plain = font.feature(FontFeature::off(b"liga"));
+        assert_eq!(shape(&plain, 20.0, "fi", None).glyphs.len(), 2);
+    }
+
+    #[test]
+    fn kern_toggle_changes_advance() {
+        let font = test_fonts::dejavu();
+        let kerned = measure_line(&font, 20.0, "AV");
+        let unkerned = measure_line(&font.feature(FontFeature::off(b"kern")), 20.0, "AV");
+        assert!(kerned < unkerned - 1.0, "{kerned} vs {unkerned}");
+    }
+
+    #[test]
+    fn one_face_text_is_a_single_source_run() {
+        let font = test_fonts::dejavu();
+        let text = "ce\u{301}l";
+        let runs = source_runs(&font, text);
+        assert_eq!(runs.len(), 1);
+        assert!(matches!(runs[0], (Source::Face(0), ref r) if *r == (0..text.len())));
+    }
+
+    #[test]
+    fn nominal_fallback_maps_chars_one_to_one() {
+        let font = test_fonts::dejavu();
+        let sf = font.arc().as_scaled(font.face_scale(0, 20.0));
+        let run = shape_nominal(&font, 0, 20.0, "abc", false);
+        let want: Vec<u16> = "abc".chars().map(|c| sf.glyph_id(c).0).collect();
+        assert_eq!(ids(&run), want);
+        let width: f32 = "abc".chars().map(|c| sf.h_advance(sf.glyph_id(c))).sum();
+        assert!((run.advance - width).abs() < 1e-3);
+        // Right-to-left runs come back in visual order.
+        let rtl = shape_nominal(&font, 0, 20.0, "abc", true);
+        assert_eq!(ids(&rtl), want.into_iter().rev().collect::<Vec<_>>());
+        // Plain text shapes to the same glyphs through the shaper.
+        assert_eq!(ids(&shape_run(&font, 0, 20.0, "abc", true)), ids(&rtl));
+    }
+
+    #[test]
+    fn mixed_direction_runs_come_out_in_visual_order() {
+        let font = test_fonts::dejavu();
+        let sf = font.arc().as_scaled(font.face_scale(0, 20.0));
+        let glyphs = |s: &str| s.chars().map(|c| sf.glyph_id(c).0).collect::<Vec<_>>();
+        let text = "abc אבג 123";
+
+        // Left-to-right paragraph: the Hebrew and the digits after it form a
+        // right-to-left run, with the number itself still read left to right.
+        let ltr = shape(&font, 20.0, text, None);
+        assert_eq!(ids(&ltr), glyphs("abc 123 גבא"));
+        assert_eq!(
+            ids(&shape(&font, 20.0, text, Some(TextDirection::Ltr))),
+            ids(&ltr)
+        );
+
+        // Right-to-left paragraph: the Latin run moves to the left end.
+        let rtl = shape(&font, 20.0, text, Some(TextDirection::Rtl));
+        assert_eq!(ids(&rtl), glyphs("123 גבא abc"));
+        assert!(rtl.glyphs.windows(2).all(|w| w[0].offset.x < w[1].offset.x));
+        assert!((rtl.advance - ltr.advance).abs() < 1e-3);
+    }
+
+    fn faces(run: &GlyphRun) -> Vec<usize> {
+        run.glyphs
+            .iter()
+            .map(|g| match g.glyph {
+                Glyph::Font { face, .. } => face,
+                Glyph::Image(_) => usize::MAX,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn missing_characters_come_from_the_fallback() {
+        let dejavu = test_fonts::dejavu();
+        let font = test_fonts::tiny().fallback(&dejavu);
+
+        // The tiny font covers `a` and `b` only, so the run splits after them.
+        let runs: Vec<_> = source_runs(&font, "abcd")
+            .into_iter()
+            .map(|(source, r)| match source {
+                Source::Face(face) => (face, r),
+                Source::Image(_) => panic!("no emoji images attached"),
+            })
+            .collect();
+        assert_eq!(runs, vec![(0, 0..2), (1, 2..4)]);
+
+        let run = shape(&font, 20.0, "abcd", None);
+        assert_eq!(faces(&run), vec![0, 0, 1, 1]);
+        let sf = dejavu.arc().as_scaled(dejavu.face_scale(0, 20.0));
+        assert_eq!(
+            ids(&run),
+            vec![1, 2, sf.glyph_id('c').0, sf.glyph_id('d').0]
+        );
+    }
+
+    #[test]
+    fn notdef_only_when_no_face_has_the_glyph() {
+        let tiny = test_fonts::tiny();
+        let font = tiny.clone().fallback(&test_fonts::dejavu());
+        // Covered by the fallback: never the primary's `.notdef`.
+        let hebrew = shape(&font, 20.0, "א", None);
+        assert_eq!(faces(&hebrew), vec![1]);
+        assert_ne!(ids(&hebrew), vec![0]);
+        // Covered by nothing in the chain: the primary font's `.notdef`.
+        let missing = shape(&font, 20.0, "\u{E000}", None);
+        assert_eq!((faces(&missing), ids(&missing)), (vec![0], vec![0]));
+        // Without the fallback, the primary's `.notdef` stands in.
+        let alone = shape(&tiny, 20.0, "c", None);
+        assert_eq!((faces(&alone), ids(&alone)), (vec![0], vec![0]));
+    }
+}
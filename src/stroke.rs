@@ -0,0 +1,430 @@
+use std::f32::consts::PI;
+
+use crate::{geom::Point, path::Polyline, shape::LineCap};
+
+/// How two connected segments of a stroked path are joined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineJoin {
+    /// Extend the outer edges until they meet, falling back to [`LineJoin::Bevel`]
+    /// when the spike would exceed [`Stroke::miter_limit`].
+    #[default]
+    Miter,
+    /// Round off the corner with a circular arc.
+    Round,
+    /// Cut the corner off with a straight line.
+    Bevel,
+}
+
+/// Options controlling how a [`crate::Path`] outline is stroked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stroke {
+    /// Full stroke width in pixels, centered on the path.
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// Maximum ratio of miter length to stroke width before a miter join is
+    /// beveled instead.
+    pub miter_limit: f32,
+    /// Alternating on/off dash lengths in pixels. Empty means a solid stroke.
+    /// An odd-length pattern is repeated to make it even, as in SVG.
+    pub dash: Vec<f32>,
+    /// Distance into the dash pattern at which each subpath starts.
+    pub dash_offset: f32,
+}
+
+impl Stroke {
+    /// A solid stroke of the given width with butt caps and miter joins.
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.0,
+            dash: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    pub fn join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+        self
+    }
+
+    pub fn miter_limit(mut self, limit: f32) -> Self {
+        self.miter_limit = limit;
+        self
+    }
+
+    /// Use a dash pattern, e.g. `&[6.0, 3.0]` for 6px dashes with 3px gaps.
+    pub fn dash(mut self, pattern: &[f32], offset: f32) -> Self {
+        self.dash = pattern.to_vec();
+        self.dash_offset = offset;
+        self
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// Turn flattened subpaths into polygons that, filled with the non-zero rule,
+/// cover the stroke outline.
+///
+/// Every segment, join and cap is emitted as its own positively-oriented polygon,
+/// so overlaps simply union under non-zero filling.
+pub(crate) fn stroke_polygons(polylines: &[Polyline], stroke: &Stroke) -> Vec<Vec<Point>> {
+    let hw = stroke.width / 2.0;
+    if hw <= 0.0 {
+        return Vec::new();
+    }
+
+    let dashed;
+    let polylines = if is_dashed(&stroke.dash) {
+        dashed = apply_dash(polylines, &stroke.dash, stroke.dash_offset);
+        &dashed
+    } else {
+        polylines
+    };
+
+    let mut out = Vec::new();
+    for poly in polylines {
+        stroke_one(poly, stroke, hw, &mut out);
+    }
+
+    for poly in &mut out {
+        if signed_area(poly) < 0.0 {
+            poly.reverse();
+        }
+    }
+    out
+}
+
+fn stroke_one(poly: &Polyline, stroke: &Stroke, hw: f32, out: &mut Vec<Vec<Point>>) {
+    let mut pts: Vec<Point> = Vec::with_capacity(poly.points.len());
+    for &p in &poly.points {
+        if pts.last().is_none_or(|q| !near(*q, p)) {
+            pts.push(p);
+        }
+    }
+    let mut closed = poly.closed;
+    if closed && pts.len() > 1 && near(pts[0], pts[pts.len() - 1]) {
+        pts.pop();
+    }
+    if pts.len() < 3 {
+        closed = false;
+    }
+
+    if pts.len() == 1 {
+        // Zero-length subpath: only caps with extent are visible.
+        let p = pts[0];
+        match stroke.cap {
+            LineCap::Butt => {}
+            LineCap::Round => out.push(circle_polygon(p, hw)),
+            LineCap::Square => out.push(vec![
+                Point::new(p.x - hw, p.y - hw),
+                Point::new(p.x + hw, p.y - hw),
+                Point::new(p.x + hw, p.y + hw),
+                Point::new(p.x - hw, p.y + hw),
+            ]),
+        }
+        return;
+    }
+
+    let n = pts.len();
+    let seg_count = if closed { n } else { n - 1 };
+    for i in 0..seg_count {
+        let a = pts[i];
+        let b = pts[(i + 1) % n];
+        let (nx, ny) = normal(a, b, hw);
+        out.push(vec![
+            Point::new(a.x + nx, a.y + ny),
+            Point::new(b.x + nx, b.y + ny),
+            Point::new(b.x - nx, b.y - ny),
+            Point::new(a.x - nx, a.y - ny),
+        ]);
+    }
+
+    let joints = if closed { 0..n } else { 1..n - 1 };
+    for i in joints {
+        let prev = pts[(i + n - 1) % n];
+        let p = pts[i];
+        let next = pts[(i + 1) % n];
+        add_join(prev, p, next, stroke, hw, out);
+    }
+
+    if !closed {
+        add_cap(pts[1], pts[0], stroke.cap, hw, out);
+        add_cap(pts[n - 2], pts[n - 1], stroke.cap, hw, out);
+    }
+}
+
+fn add_join(
+    prev: Point,
+    p: Point,
+    next: Point,
+    stroke: &Stroke,
+    hw: f32,
+    out: &mut Vec<Vec<Point>>,
+) {
+    let (d0x, d0y) = unit(prev, p);
+    let (d1x, d1y) = unit(p, next);
+    let cross = d0x * d1y - d0y * d1x;
+    let dot = d0x * d1x + d0y * d1y;
+    if cross.abs() < 1e-4 && dot > 0.0 {
+        return; // Collinear: the segment quads already meet flush.
+    }
+
+    if stroke.join == LineJoin::Round {
+        out.push(circle_polygon(p, hw));
+        return;
+    }
+
+    // The outer side of the turn is opposite the direction of rotation.
+    let s = if cross > 0.0 { -hw } else { hw };
+    let (n0x, n0y) = (-d0y * s, d0x * s);
+    let (n1x, n1y) = (-d1y * s, d1x * s);
+    let a = Point::new(p.x + n0x, p.y + n0y);
+    let b = Point::new(p.x + n1x, p.y + n1y);
+
+    if stroke.join == LineJoin::Miter {
+        let (mx, my) = (n0x + n1x, n0y + n1y);
+        let ml = f32::hypot(mx, my);
+        if ml > 1e-6 {
+            // cos(θ/2) between the offset normal and the miter direction.
+            let cos_half = (mx * n0x + my * n0y) / (ml * hw);
+            if cos_half > 0.0 && 1.0 / cos_half <= stroke.miter_limit {
+                let len = hw / cos_half;
+                let tip = Point::new(p.x + mx / ml * len, p.y + my / ml * len);
+                out.push(vec![p, a, tip, b]);
+                return;
+            }
+        }
+    }
+
+    out.push(vec![p, a, b]);
+}
+
+/// Add a cap at `end`, where the stroke arrives from the direction of `from`.
+fn add_cap(from: Point, end: Point, cap: LineCap, hw: f32, out: &mut Vec<Vec<Point>>) {
+    match cap {
+        LineCap::Butt => {}
+        LineCap::Round => out.push(circle_polygon(end, hw)),
+        LineCap::Square => {
+            let (dx, dy) = unit(from, end);
+            let (nx, ny) = (-dy * hw, dx * hw);
+            let (ex, ey) = (dx * hw, dy * hw);
+            out.push(vec![
+                Point::new(end.x + nx, end.y + ny),
+                Point::new(end.x + nx + ex, end.y + ny + ey),
+                Point::new(end.x - nx + ex, end.y - ny + ey),
+                Point::new(end.x - nx, end.y - ny),
+            ]);
+        }
+    }
+}
+
+/// Patterns shorter than this in total are drawn solid; the dash walk could
+/// not advance through them in `f32`.
+const MIN_DASH_PATTERN: f32 = 0.01;
+
+/// Most dash boundaries crossed per subpath. Anything past it is dropped, so a
+/// dense pattern on a long path can't emit unbounded geometry.
+const MAX_DASH_STEPS: usize = 20_000;
+
+/// Split polylines into the "on" intervals of a dash pattern.
+fn apply_dash(polylines: &[Polyline], pattern: &[f32], offset: f32) -> Vec<Polyline> {
+    let mut pattern = pattern.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_within(..);
+    }
+    let total: f32 = pattern.iter().sum();
+
+    let mut out = Vec::new();
+    for poly in polylines {
+        let mut pts = poly.points.clone();
+        if poly.closed && pts.len() > 1 {
+            pts.push(pts[0]);
+        }
+
+        // Find where in the pattern this subpath starts.
+        let mut idx = 0;
+        let mut remaining = pattern[0];
+        let mut skip = offset.rem_euclid(total);
+        while skip > 0.0 {
+            if skip < remaining {
+                remaining -= skip;
+                break;
+            }
+            skip -= remaining;
+            idx = (idx + 1) % pattern.len();
+            remaining = pattern[idx];
+        }
+
+        let mut current: Vec<Point> = Vec::new();
+        if idx % 2 == 0 {
+            current.push(pts[0]);
+        }
+
+        let mut steps = 0;
+        'walk: for w in pts.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let seg_len = f32::hypot(b.x - a.x, b.y - a.y);
+            let mut pos = 0.0;
+            while seg_len - pos > remaining {
+                steps += 1;
+                if steps > MAX_DASH_STEPS {
+                    break 'walk;
+                }
+                pos += remaining;
+                let t = pos / seg_len;
+                let p = Point::new(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+                if idx % 2 == 0 {
+                    current.push(p);
+                    out.push(Polyline {
+                        points: std::mem::take(&mut current),
+                        closed: false,
+                    });
+                } else {
+                    current.push(p);
+                }
+                idx = (idx + 1) % pattern.len();
+                remaining = pattern[idx];
+            }
+            remaining -= seg_len - pos;
+            if idx % 2 == 0 {
+                current.push(b);
+            }
+        }
+
+        if idx % 2 == 0 && !current.is_empty() {
+            out.push(Polyline {
+                points: current,
+                closed: false,
+            });
+        }
+    }
+    out
+}
+
+fn is_dashed(pattern: &[f32]) -> bool {
+    !pattern.is_empty()
+        && pattern.iter().all(|d| d.is_finite() && *d >= 0.0)
+        && pattern.iter().sum::<f32>() >= MIN_DASH_PATTERN
+}
+
+fn circle_polygon(c: Point, r: f32) -> Vec<Point> {
+    // Keep the chord error under a tenth of a pixel.
+    let step = 2.0 * (1.0 - 0.1 / r.max(0.2)).clamp(-1.0, 1.0).acos();
+    let n = ((2.0 * PI / step).ceil() as usize).clamp(8, 256);
+    (0..n)
+        .map(|i| {
+            let a = i as f32 / n as f32 * 2.0 * PI;
+            Point::new(c.x + r * a.cos(), c.y + r * a.sin())
+        })
+        .collect()
+}
+
+fn signed_area(poly: &[Point]) -> f32 {
+    let mut area = 0.0;
+    for (i, a) in poly.iter().enumerate() {
+        let b = poly[(i + 1) % poly.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+    area / 2.0
+}
+
+fn unit(a: Point, b: Point) -> (f32, f32) {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let len = f32::hypot(dx, dy);
+    (dx / len, dy / len)
+}
+
+/// Left-hand normal of segment `a → b`, scaled to length `hw`.
+fn normal(a: Point, b: Point, hw: f32) -> (f32, f32) {
+    let (dx, dy) = unit(a, b);
+    (-dy * hw, dx * hw)
+}
+
+fn near(a: Point, b: Point) -> bool {
+    (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(points: &[(f32, f32)]) -> Polyline {
+        Polyline {
+            points: points.iter().map(|&p| p.into()).collect(),
+            closed: false,
+        }
+    }
+
+    #[test]
+    fn polygons_are_positively_oriented() {
+        let polys = stroke_polygons(
+            &[line(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)])],
+            &Stroke::new(2.0).cap(LineCap::Round),
+        );
+        assert!(!polys.is_empty());
+        assert!(polys.iter().all(|p| signed_area(p) > 0.0));
+    }
+
+    #[test]
+    fn dash_splits_line() {
+        let dashed = apply_dash(&[line(&[(0.0, 0.0), (10.0, 0.0)])], &[2.0, 3.0], 0.0);
+        // On: 0–2 and 5–7; the line ends inside the following gap.
+        assert_eq!(dashed.len(), 2);
+        assert_eq!(
+            dashed[0].points,
+            vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0)]
+        );
+        assert_eq!(
+            dashed[1].points,
+            vec![Point::new(5.0, 0.0), Point::new(7.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn dash_offset_shifts_pattern() {
+        let dashed = apply_dash(&[line(&[(0.0, 0.0), (10.0, 0.0)])], &[2.0, 3.0], 3.0);
+        // Offset 3 starts two pixels into the gap.
+        assert_eq!(
+            dashed[0].points,
+            vec![Point::new(2.0, 0.0), Point::new(4.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn tiny_dash_pattern_is_solid() {
+        let stroke = Stroke::new(2.0).dash(&[1e-6, 1e-6], 0.0);
+        assert!(!is_dashed(&stroke.dash));
+        let polys = stroke_polygons(&[line(&[(0.0, 0.0), (100.0, 0.0)])], &stroke);
+        assert_eq!(polys.len(), 1);
+    }
+
+    #[test]
+    fn dash_count_is_capped() {
+        let dashed = apply_dash(&[line(&[(0.0, 0.0), (1e6, 0.0)])], &[0.005, 0.005], 0.0);
+        assert!(dashed.len() <= MAX_DASH_STEPS / 2 + 1);
+        assert!(dashed.iter().map(|d| d.points.len()).sum::<usize>() <= MAX_DASH_STEPS + 2);
+    }
+
+    #[test]
+    fn sharp_miter_falls_back_to_bevel() {
+        let poly = [line(&[(0.0, 0.0), (20.0, 0.0), (0.0, 1.0)])];
+        let miter = stroke_polygons(&poly, &Stroke::new(2.0).miter_limit(100.0));
+        let bevel = stroke_polygons(&poly, &Stroke::new(2.0));
+        let has_quad_join = |polys: &[Vec<Point>]| polys.iter().filter(|p| p.len() == 4).count();
+        // Two segment quads either way; the unlimited miter adds a third quad.
+        assert_eq!(has_quad_join(&miter), 3);
+        assert_eq!(has_quad_join(&bevel), 2);
+    }
+}
@@ -0,0 +1,139 @@
+use image::RgbaImage;
+use resvg::{tiny_skia, usvg};
+
+use crate::{bitmap::ImageFit, blend::BlendMode, color::Color, error::RenderError};
+
+/// Options controlling how an SVG document is drawn onto the canvas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgOptions {
+    pub fit: ImageFit,
+    /// Opacity multiplier in `[0, 1]`.
+    pub opacity: f32,
+    /// Value for `currentColor` in the document, for recoloring monochrome icon
+    /// sets. `None` keeps the document's own `color` (black by default).
+    pub current_color: Option<Color>,
+    /// Blend mode for this draw. `None` uses the canvas's current blend mode.
+    pub blend_mode: Option<BlendMode>,
+}
+
+impl SvgOptions {
+    /// Contain-fit, fully opaque, document colors untouched.
+    pub fn new() -> Self {
+        Self {
+            fit: ImageFit::Contain,
+            opacity: 1.0,
+            current_color: None,
+            blend_mode: None,
+        }
+    }
+
+    pub fn fit(mut self, fit: ImageFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn current_color(mut self, color: Color) -> Self {
+        self.current_color = Some(color);
+        self
+    }
+
+    pub fn blend_mode(mut self, mode: BlendMode) -> Self {
+        self.blend_mode = Some(mode);
+        self
+    }
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse an SVG document, applying the `currentColor` override if any.
+pub(crate) fn parse(data: &[u8], opts: &SvgOptions) -> Result<usvg::Tree, RenderError> {
+    let mut usvg_opts = usvg::Options::default();
+    if let Some(c) = opts.current_color {
+        // A user style sheet on the root sets the inherited `color` property.
+        usvg_opts.style_sheet = Some(format!(
+            "svg {{ color: rgba({}, {}, {}, {}) }}",
+            c.r,
+            c.g,
+            c.b,
+            c.a as f32 / 255.0
+        ));
+    }
+    Ok(usvg::Tree::from_data(data, &usvg_opts)?)
+}
+
+/// Rasterize the `w × h` pixel window at `(x, y)` of `tree` scaled to
+/// `width × height` pixels (straight alpha), so only the visible part of a
+/// large placement is rendered. `None` if the window can't be allocated.
+pub(crate) fn render(
+    tree: &usvg::Tree,
+    width: u32,
+    height: u32,
+    (x, y, w, h): (u32, u32, u32, u32),
+) -> Option<RgbaImage> {
+    let mut pixmap = tiny_skia::Pixmap::new(w, h)?;
+    let size = tree.size();
+    let transform = tiny_skia::Transform::from_scale(
+        width as f32 / size.width(),
+        height as f32 / size.height(),
+    )
+    .post_translate(-(x as f32), -(y as f32));
+    resvg::render(tree, transform, &mut pixmap.as_mut());
+
+    let mut out = RgbaImage::new(w, h);
+    for (dst, src) in out.pixels_mut().zip(pixmap.pixels()) {
+        let c = src.demultiply();
+        *dst = image::Rgba([c.red(), c.green(), c.blue(), c.alpha()]);
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICON: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <rect width="10" height="10" fill="currentColor"/>
+    </svg>"#;
+
+    #[test]
+    fn renders_at_requested_size() {
+        let tree = parse(ICON.as_bytes(), &SvgOptions::new()).unwrap();
+        let img = render(&tree, 20, 20, (0, 0, 20, 20)).unwrap();
+        assert_eq!(img.dimensions(), (20, 20));
+        assert_eq!(img.get_pixel(10, 10).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn renders_only_the_requested_window() {
+        const HALVES: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+            <rect width="5" height="10" fill="red"/>
+            <rect x="5" width="5" height="10" fill="blue"/>
+        </svg>"#;
+        let tree = parse(HALVES.as_bytes(), &SvgOptions::new()).unwrap();
+        let img = render(&tree, 100_000, 100_000, (50_000, 0, 4, 4)).unwrap();
+        assert_eq!(img.dimensions(), (4, 4));
+        assert_eq!(img.get_pixel(1, 1).0, [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn current_color_recolors() {
+        let opts = SvgOptions::new().current_color(Color::rgb(255, 0, 0));
+        let tree = parse(ICON.as_bytes(), &opts).unwrap();
+        let img = render(&tree, 10, 10, (0, 0, 10, 10)).unwrap();
+        assert_eq!(img.get_pixel(5, 5).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn invalid_document_errors() {
+        assert!(parse(b"not svg", &SvgOptions::new()).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+//! Fonts for unit tests.
+
+use crate::font::{FontHandle, FontRegistry};
+
+/// DejaVu Sans: Latin with `liga` and GPOS `kern`, Hebrew, and a monochrome
+/// U+1F600.
+pub(crate) const DEJAVU_SANS: &[u8] = include_bytes!("../testdata/DejaVuSans.ttf");
+
+pub(crate) fn dejavu() -> FontHandle {
+    FontRegistry::new()
+        .load_bytes("dejavu", DEJAVU_SANS)
+        .unwrap()
+}
+
+/// The COLRv1 test font from Google's color font test suite: every glyph is a
+/// COLR paint graph, mapped from U+F0100 up.
+pub(crate) fn colr() -> FontHandle {
+    FontRegistry::new()
+        .load_bytes("colr", include_bytes!("../testdata/colr_1.ttf"))
+        .unwrap()
+}
+
+/// Color of the U+1F600 bitmap in [`tiny`].
+pub(crate) const TINY_EMOJI_COLOR: [u8; 4] = [0, 0, 255, 255];
+
+/// A generated font with 1000 units per em and one-em advances that covers
+/// only `a`, `b` and U+1F600. The letters have no outlines; the emoji is a
+/// 16 px sbix PNG filled with [`TINY_EMOJI_COLOR`].
+pub(crate) fn tiny() -> FontHandle {
+    FontRegistry::new().load_vec("tiny", tiny_font()).unwrap()
+}
+
+pub(crate) fn tiny_font() -> Vec<u8> {
+    // Glyphs: 0 .notdef, 1 `a`, 2 `b`, 3 U+1F600.
+    const GLYPHS: u16 = 4;
+    let be16 = |v: u16| v.to_be_bytes();
+    let be32 = |v: u32| v.to_be_bytes();
+
+    let mut head = Vec::new();
+    head.extend(be32(0x0001_0000)); // version
+    head.extend(be32(0x0001_0000)); // fontRevision
+    head.extend(be32(0)); // checksumAdjustment
+    head.extend(be32(0x5F0F_3CF5)); // magicNumber
+    head.extend(be16(0)); // flags
+    head.extend(be16(1000)); // unitsPerEm
+    head.extend([0; 16]); // created, modified
+    for v in [0i16, -200, 1000, 800] {
+        head.extend(v.to_be_bytes()); // xMin, yMin, xMax, yMax
+    }
+    head.extend([0; 10]); // macStyle .. glyphDataFormat
+
+    let mut hhea = Vec::new();
+    hhea.extend(be32(0x0001_0000));
+    for v in [800i16, -200, 0] {
+        hhea.extend(v.to_be_bytes()); // ascender, descender, lineGap
+    }
+    hhea.extend(be16(1000)); // advanceWidthMax
+    hhea.extend([0; 6]); // minLeftSideBearing .. xMaxExtent
+    hhea.extend(be16(1)); // caretSlopeRise
+    hhea.extend([0; 14]); // caretSlopeRun .. metricDataFormat
+    hhea.extend(be16(GLYPHS)); // numberOfHMetrics
+
+    let mut maxp = be32(0x0000_5000).to_vec();
+    maxp.extend(be16(GLYPHS));
+
+    let hmtx: Vec<u8> = (0..GLYPHS).flat_map(|_| [0x03, 0xE8, 0, 0]).collect();
+
+    // A format 12 subtable, so the emoji outside the BMP can be mapped.
+    let groups: [(u32, u32, u32); 2] = [('a' as u32, 'b' as u32, 1), (0x1F600, 0x1F600, 3)];
+    let mut cmap = Vec::new();
+    cmap.extend(be16(0));
+    cmap.extend(be16(1));
+    cmap.extend(be16(3));
+    cmap.extend(be16(10));
+    cmap.extend(be32(12));
+    cmap.extend(be16(12));
+    cmap.extend(be16(0));
+    cmap.extend(be32(16 + 12 * groups.len() as u32));
+    cmap.extend(be32(0)); // language
+    cmap.extend(be32(groups.len() as u32));
+    for (start, end, glyph) in groups {
+        cmap.extend(be32(start));
+        cmap.extend(be32(end));
+        cmap.extend(be32(glyph));
+    }
+
+    let mut png = Vec::new();
+    image::RgbaImage::from_pixel(16, 16, image::Rgba(TINY_EMOJI_COLOR))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    // One 16 ppem strike; only the last glyph has data.
+    let data_start = 4 + 4 * (GLYPHS as u32 + 1);
+    let data_end = data_start + 8 + png.len() as u32;
+    let mut sbix = Vec::new();
+    sbix.extend(be16(1)); // version
+    sbix.extend(be16(0)); // flags
+    sbix.extend(be32(1)); // numStrikes
+    sbix.extend(be32(12)); // strike offset
+    sbix.extend(be16(16)); // ppem
+    sbix.extend(be16(72)); // ppi
+    for offset in [data_start, data_start, data_start, data_start, data_end] {
+        sbix.extend(be32(offset));
+    }
+    sbix.extend([0; 4]); // originOffsetX, originOffsetY
+    sbix.extend(*b"png ");
+    sbix.extend(png);
+
+    let tables: [(&[u8; 4], Vec<u8>); 6] = [
+        (b"cmap", cmap),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"maxp", maxp),
+        (b"sbix", sbix),
+    ];
+    let mut font = Vec::new();
+    font.extend(be32(0x0001_0000));
+    font.extend(be16(tables.len() as u16));
+    font.extend([0; 6]); // searchRange, entrySelector, rangeShift
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        font.extend(*tag);
+        font.extend(be32(0)); // checksum
+        font.extend(be32(offset as u32));
+        font.extend(be32(data.len() as u32));
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        font.extend(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+    font
+}
@@ -0,0 +1,174 @@
+use crate::geom::{Point, Rect};
+
+/// A 2D affine transform, stored as the matrix
+///
+/// ```text
+/// | a  c  e |
+/// | b  d  f |
+/// | 0  0  1 |
+/// ```
+///
+/// mapping `(x, y)` to `(a·x + c·y + e, b·x + d·y + f)` — the same layout as the
+/// CSS/SVG `matrix(a, b, c, d, e, f)` function. Angles are in radians and, with
+/// y pointing down, positive rotations turn clockwise on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    pub const fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub const fn translate(tx: f32, ty: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    pub const fn scale(sx: f32, sy: f32) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// Rotate by `angle` around `center` instead of the origin.
+    pub fn rotate_about(angle: f32, center: Point) -> Self {
+        Self::translate(center.x, center.y)
+            .then(Self::rotate(angle))
+            .then(Self::translate(-center.x, -center.y))
+    }
+
+    /// Shear by `ax` radians along the x axis and `ay` radians along the y axis,
+    /// as CSS `skew(ax, ay)`.
+    pub fn skew(ax: f32, ay: f32) -> Self {
+        Self::new(1.0, ay.tan(), ax.tan(), 1.0, 0.0, 0.0)
+    }
+
+    /// `self × other`: apply `other` first, then `self`.
+    ///
+    /// This is how [`crate::Canvas::translate`] and friends combine a new
+    /// transform with the current one, so later calls act in local coordinates.
+    pub fn then(self, other: Transform) -> Self {
+        Self::new(
+            self.a * other.a + self.c * other.b,
+            self.b * other.a + self.d * other.b,
+            self.a * other.c + self.c * other.d,
+            self.b * other.c + self.d * other.d,
+            self.a * other.e + self.c * other.f + self.e,
+            self.b * other.e + self.d * other.f + self.f,
+        )
+    }
+
+    /// The inverse transform, or `None` if it collapses the plane (zero determinant).
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.a * self.d - self.b * self.c;
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        let inv = 1.0 / det;
+        Some(Self::new(
+            self.d * inv,
+            -self.b * inv,
+            -self.c * inv,
+            self.a * inv,
+            (self.c * self.f - self.d * self.e) * inv,
+            (self.b * self.e - self.a * self.f) * inv,
+        ))
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    pub fn map_point(&self, p: Point) -> Point {
+        Point::new(
+            self.a * p.x + self.c * p.y + self.e,
+            self.b * p.x + self.d * p.y + self.f,
+        )
+    }
+
+    /// Axis-aligned bounding box of `rect` after transforming its corners.
+    pub fn map_rect(&self, rect: Rect) -> Rect {
+        let corners = [
+            Point::new(rect.x, rect.y),
+            Point::new(rect.right(), rect.y),
+            Point::new(rect.right(), rect.bottom()),
+            Point::new(rect.x, rect.bottom()),
+        ]
+        .map(|p| self.map_point(p));
+        let (mut l, mut t) = (f32::INFINITY, f32::INFINITY);
+        let (mut r, mut b) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for p in corners {
+            l = l.min(p.x);
+            t = t.min(p.y);
+            r = r.max(p.x);
+            b = b.max(p.y);
+        }
+        Rect::from_ltrb(l, t, r, b)
+    }
+
+    /// Average linear scale factor: the square root of the absolute determinant.
+    pub(crate) fn mean_scale(&self) -> f32 {
+        (self.a * self.d - self.b * self.c).abs().sqrt()
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn close(a: Point, b: Point) -> bool {
+        (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4
+    }
+
+    #[test]
+    fn then_applies_right_operand_first() {
+        let t = Transform::translate(10.0, 0.0).then(Transform::scale(2.0, 2.0));
+        assert!(close(
+            t.map_point(Point::new(1.0, 1.0)),
+            Point::new(12.0, 2.0)
+        ));
+    }
+
+    #[test]
+    fn rotation_is_clockwise_on_screen() {
+        let p = Transform::rotate(FRAC_PI_2).map_point(Point::new(1.0, 0.0));
+        assert!(close(p, Point::new(0.0, 1.0)));
+    }
+
+    #[test]
+    fn rotate_about_keeps_center_fixed() {
+        let c = Point::new(72.0, 72.0);
+        let t = Transform::rotate_about(1.0, c);
+        assert!(close(t.map_point(c), c));
+    }
+
+    #[test]
+    fn invert_round_trips() {
+        let t = Transform::translate(5.0, -3.0)
+            .then(Transform::rotate(0.7))
+            .then(Transform::skew(0.3, 0.0))
+            .then(Transform::scale(2.0, 0.5));
+        let p = Point::new(3.0, 4.0);
+        assert!(close(t.invert().unwrap().map_point(t.map_point(p)), p));
+        assert!(Transform::scale(0.0, 1.0).invert().is_none());
+    }
+}
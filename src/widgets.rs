@@ -0,0 +1,955 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use ab_glyph::{Font, ScaleFont};
+
+use crate::{
+    canvas::{Canvas, HAlign, TextOptions, VAlign},
+    color::Color,
+    error::RenderError,
+    font::FontHandle,
+    geom::{Corner, Rect},
+    layout::{TextLine, measure_line},
+    paint::Paint,
+    path::{FillRule, Path},
+    shape::LineCap,
+    stroke::{LineJoin, Stroke},
+};
+
+/// Direction a [`ProgressBar`] fills in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// Fills left to right.
+    #[default]
+    Horizontal,
+    /// Fills bottom to top.
+    Vertical,
+}
+
+/// A straight progress bar: a track with a filled portion proportional to `value`.
+///
+/// Draw with [`Canvas::draw_progress_bar`].
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{Color, ProgressBar, Rect};
+///
+/// let bar = ProgressBar::new(0.42)
+///     .fill(Color::rgb(0, 200, 120))
+///     .radius(6.0)
+///     .label(font, 14.0);
+/// canvas.draw_progress_bar(Rect::new(12.0, 100.0, 120.0, 20.0), &bar)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProgressBar {
+    /// Fraction filled, clamped to `[0, 1]` when drawn.
+    pub value: f32,
+    pub orientation: Orientation,
+    /// Paint for the unfilled track.
+    pub track: Paint,
+    /// Paint for the filled portion. Gradients span the whole track, so the
+    /// visible color follows the value.
+    pub fill: Paint,
+    /// Corner radius of the track in pixels. The fill is clipped to it.
+    pub radius: f32,
+    /// Value text centered on the bar.
+    pub label: Option<ValueLabel>,
+}
+
+impl ProgressBar {
+    /// A horizontal bar with a faint track and a blue fill, square corners and no label.
+    pub fn new(value: f32) -> Self {
+        Self {
+            value,
+            orientation: Orientation::Horizontal,
+            track: Paint::Solid(Color::rgba(255, 255, 255, 48)),
+            fill: Paint::Solid(Color::rgb(0, 150, 255)),
+            radius: 0.0,
+            label: None,
+        }
+    }
+
+    pub fn vertical(mut self) -> Self {
+        self.orientation = Orientation::Vertical;
+        self
+    }
+
+    pub fn track(mut self, paint: impl Into<Paint>) -> Self {
+        self.track = paint.into();
+        self
+    }
+
+    pub fn fill(mut self, paint: impl Into<Paint>) -> Self {
+        self.fill = paint.into();
+        self
+    }
+
+    /// Round the track's corners; half the bar's thickness gives pill ends.
+    pub fn radius(mut self, r: f32) -> Self {
+        self.radius = r;
+        self
+    }
+
+    /// Show the value as a percentage in `font` at `size` pixels.
+    pub fn label(mut self, font: FontHandle, size: f32) -> Self {
+        self.label = Some(ValueLabel::new(font, size));
+        self
+    }
+
+    /// Replace the value label, e.g. for custom text or color.
+    pub fn value_label(mut self, label: ValueLabel) -> Self {
+        self.label = Some(label);
+        self
+    }
+}
+
+/// A circular ring or arc gauge filled clockwise from `start_angle` in
+/// proportion to `value`.
+///
+/// Angles are in radians with zero along +X and increasing clockwise on
+/// screen, as in [`Path::arc`]. Draw with [`Canvas::draw_gauge`].
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{Color, Gauge};
+///
+/// // Speedometer-style 270° arc with the value in the middle.
+/// let gauge = Gauge::arc(0.7, 0.75 * PI, 2.25 * PI)
+///     .thickness(14.0)
+///     .fill(Color::rgb(255, 140, 0))
+///     .label(font, 28.0);
+/// canvas.draw_gauge(Rect::new(0.0, 0.0, 144.0, 144.0), &gauge)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Gauge {
+    /// Fraction filled, clamped to `[0, 1]` when drawn.
+    pub value: f32,
+    pub start_angle: f32,
+    pub end_angle: f32,
+    /// Stroke width of the ring in pixels. The ring is inset so the stroke
+    /// stays inside the rect.
+    pub thickness: f32,
+    pub track: Paint,
+    pub fill: Paint,
+    /// Cap at the ends of the track and fill. Ignored on a full ring.
+    pub cap: LineCap,
+    /// Value text centered in the ring.
+    pub label: Option<ValueLabel>,
+}
+
+impl Gauge {
+    /// A full ring starting at 12 o'clock.
+    pub fn ring(value: f32) -> Self {
+        Self::arc(value, -FRAC_PI_2, -FRAC_PI_2 + TAU)
+    }
+
+    /// An arc from `start_angle` to `end_angle`, with round caps.
+    pub fn arc(value: f32, start_angle: f32, end_angle: f32) -> Self {
+        Self {
+            value,
+            start_angle,
+            end_angle,
+            thickness: 12.0,
+            track: Paint::Solid(Color::rgba(255, 255, 255, 48)),
+            fill: Paint::Solid(Color::rgb(0, 150, 255)),
+            cap: LineCap::Round,
+            label: None,
+        }
+    }
+
+    pub fn thickness(mut self, t: f32) -> Self {
+        self.thickness = t;
+        self
+    }
+
+    pub fn track(mut self, paint: impl Into<Paint>) -> Self {
+        self.track = paint.into();
+        self
+    }
+
+    pub fn fill(mut self, paint: impl Into<Paint>) -> Self {
+        self.fill = paint.into();
+        self
+    }
+
+    pub fn cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Show the value as a percentage in `font` at `size` pixels.
+    pub fn label(mut self, font: FontHandle, size: f32) -> Self {
+        self.label = Some(ValueLabel::new(font, size));
+        self
+    }
+
+    /// Replace the value label, e.g. for custom text or color.
+    pub fn value_label(mut self, label: ValueLabel) -> Self {
+        self.label = Some(label);
+        self
+    }
+}
+
+/// Value text drawn on a widget: centered on bars and gauges, in the top-right
+/// corner of charts. Shows the value unless `text` is set.
+#[derive(Debug, Clone)]
+pub struct ValueLabel {
+    pub text: Option<String>,
+    /// Appended to the automatic value text. `None` means `%` on bars and
+    /// gauges and nothing on charts.
+    pub suffix: Option<String>,
+    /// Font, size and color. Alignment is set by the widget.
+    pub options: TextOptions,
+}
+
+impl ValueLabel {
+    /// White value text.
+    pub fn new(font: FontHandle, size: f32) -> Self {
+        Self {
+            text: None,
+            suffix: None,
+            options: TextOptions::new(font, size),
+        }
+    }
+
+    /// Show `text` instead of the value.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Unit after the value, e.g. `"ms"` or `"°"`.
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    pub fn color(mut self, c: impl Into<Paint>) -> Self {
+        self.options = self.options.color(c);
+        self
+    }
+
+    /// Draw the label in `rect`, showing `value` followed by the suffix or
+    /// `default_suffix`.
+    fn draw(
+        &self,
+        canvas: &mut Canvas,
+        rect: Rect,
+        value: String,
+        default_suffix: &str,
+        (h_align, v_align): (HAlign, VAlign),
+    ) -> Result<(), RenderError> {
+        let text = match &self.text {
+            Some(t) => t.clone(),
+            None => value + self.suffix.as_deref().unwrap_or(default_suffix),
+        };
+        let opts = &self.options;
+        let line = TextLine {
+            width_px: measure_line(&opts.font, opts.size, &text),
+            text,
+        };
+        let opts = opts.clone().h_align(h_align).v_align(v_align);
+        canvas.draw_text_in(rect, &[line], &opts).map(|_| ())
+    }
+
+    /// Draw a fraction centered in `rect` as a whole percentage.
+    fn draw_percent(&self, canvas: &mut Canvas, rect: Rect, value: f32) -> Result<(), RenderError> {
+        let value = format!("{:.0}", value * 100.0);
+        let centered = (HAlign::Center, VAlign::Center);
+        self.draw(canvas, rect, value, "%", centered)
+    }
+}
+
+/// How a [`Chart`] draws its series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChartKind {
+    /// A polyline through the values.
+    #[default]
+    Line,
+    /// A polyline with the area beneath it filled.
+    Area,
+    /// One bar per value, e.g. a histogram.
+    Bars,
+}
+
+/// A compact chart of a data series: sparkline, area chart or bar chart.
+///
+/// Values are spaced evenly left to right, oldest first. Non-finite values
+/// leave a gap. Draw with [`Canvas::draw_chart`].
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{Chart, Color, ValueLabel};
+///
+/// // Ping history: amber above 80 ms, red above 150 ms.
+/// let chart = Chart::area(&pings)
+///     .range(0.0, 200.0)
+///     .threshold(80.0, Color::rgb(255, 180, 0))
+///     .threshold(150.0, Color::rgb(255, 60, 60))
+///     .value_label(ValueLabel::new(font, 16.0).suffix("ms"));
+/// canvas.draw_chart(Rect::new(8.0, 40.0, 128.0, 96.0), &chart)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Chart {
+    pub kind: ChartKind,
+    pub values: Vec<f32>,
+    /// Values mapped to the bottom and top of the rect, in either order.
+    /// `None` scales to the data; bar charts then also include zero so bars
+    /// grow from a baseline.
+    pub range: Option<(f32, f32)>,
+    /// Line and bar paint below the first threshold.
+    pub color: Paint,
+    /// Paint beneath the line of an area chart. `None` uses a translucent
+    /// `color`, or `color` itself when it is a gradient.
+    pub area: Option<Paint>,
+    /// Line width in pixels.
+    pub line_width: f32,
+    /// Gap between bars in pixels.
+    pub bar_gap: f32,
+    /// `(value, color)` pairs, ascending: whatever lies at or above a threshold
+    /// is drawn in its color. Private so [`Chart::threshold`] keeps it sorted.
+    thresholds: Vec<(f32, Color)>,
+    /// The last value, drawn in the top-right corner.
+    pub label: Option<ValueLabel>,
+}
+
+impl Chart {
+    /// A line chart (sparkline).
+    pub fn line(values: &[f32]) -> Self {
+        Self::new(ChartKind::Line, values)
+    }
+
+    /// A line chart with the area beneath the line filled.
+    pub fn area(values: &[f32]) -> Self {
+        Self::new(ChartKind::Area, values)
+    }
+
+    /// A bar chart.
+    pub fn bars(values: &[f32]) -> Self {
+        Self::new(ChartKind::Bars, values)
+    }
+
+    /// A blue chart of `kind`, scaled to the data.
+    pub fn new(kind: ChartKind, values: &[f32]) -> Self {
+        Self {
+            kind,
+            values: values.to_vec(),
+            range: None,
+            color: Paint::Solid(Color::rgb(0, 150, 255)),
+            area: None,
+            line_width: 2.0,
+            bar_gap: 1.0,
+            thresholds: Vec::new(),
+            label: None,
+        }
+    }
+
+    /// Fix the scale instead of fitting it to the data. Bounds given the
+    /// wrong way round are swapped.
+    pub fn range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn color(mut self, paint: impl Into<Paint>) -> Self {
+        self.color = paint.into();
+        self
+    }
+
+    pub fn area_fill(mut self, paint: impl Into<Paint>) -> Self {
+        self.area = Some(paint.into());
+        self
+    }
+
+    pub fn line_width(mut self, w: f32) -> Self {
+        self.line_width = w;
+        self
+    }
+
+    pub fn bar_gap(mut self, gap: f32) -> Self {
+        self.bar_gap = gap;
+        self
+    }
+
+    /// Color everything at or above `value` with `color`. Can be called repeatedly.
+    pub fn threshold(mut self, value: f32, color: Color) -> Self {
+        self.thresholds.push((value, color));
+        self.thresholds.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self
+    }
+
+    /// Show the last value in `font` at `size` pixels.
+    pub fn label(mut self, font: FontHandle, size: f32) -> Self {
+        self.label = Some(ValueLabel::new(font, size));
+        self
+    }
+
+    /// Replace the value label, e.g. to add a unit suffix.
+    pub fn value_label(mut self, label: ValueLabel) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// The `(min, max)` mapped onto the rect's height, never empty.
+    fn scale(&self) -> (f32, f32) {
+        let (lo, hi) = self
+            .range
+            .map(|(a, b)| (a.min(b), a.max(b)))
+            .unwrap_or_else(|| {
+                let finite = self.values.iter().copied().filter(|v| v.is_finite());
+                let (lo, hi) = finite.fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), v| {
+                    (lo.min(v), hi.max(v))
+                });
+                match self.kind {
+                    ChartKind::Bars => (lo.min(0.0), hi.max(0.0)),
+                    _ if lo > hi => (0.0, 1.0),
+                    _ => (lo, hi),
+                }
+            });
+        if hi > lo {
+            (lo, hi)
+        } else {
+            (lo - 1.0, lo + 1.0)
+        }
+    }
+
+    /// Paint for a bar of `value`: the color of the highest threshold it reaches.
+    fn color_for(&self, value: f32) -> Paint {
+        match self.thresholds.iter().rev().find(|(t, _)| value >= *t) {
+            Some((_, color)) => Paint::Solid(*color),
+            None => self.color.clone(),
+        }
+    }
+}
+
+/// A notification badge: a count in a pill or circle, or a plain dot, in a
+/// corner of a rect.
+///
+/// Counts above `max` show as e.g. `99+`, and long counts shrink their text
+/// rather than growing the pill without bound. Draw with [`Canvas::draw_badge`].
+///
+/// # Example
+/// ```rust,ignore
+/// use streamdeck_render::{Badge, Color, Rect};
+///
+/// let badge = Badge::count(font, unread).outline(3.0, Color::BLACK);
+/// canvas.draw_badge(Rect::new(0.0, 0.0, 144.0, 144.0), &badge)?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Badge {
+    /// Font for the count. `None` draws a dot.
+    pub font: Option<FontHandle>,
+    pub count: u32,
+    /// Largest count shown exactly; anything above reads `{max}+`.
+    pub max: u32,
+    pub corner: Corner,
+    /// Height of the pill, and the diameter of a dot or one-digit badge, in pixels.
+    pub size: f32,
+    /// Distance from the rect's edges to the badge, in pixels.
+    pub margin: f32,
+    pub color: Paint,
+    pub text_color: Paint,
+    /// Ring drawn around the badge to set it off from the background:
+    /// `(width, paint)`.
+    pub outline: Option<(f32, Paint)>,
+}
+
+impl Badge {
+    /// A red badge showing `count`. Nothing is drawn for a count of zero.
+    pub fn count(font: FontHandle, count: u32) -> Self {
+        Self {
+            font: Some(font),
+            count,
+            max: 99,
+            corner: Corner::TopRight,
+            size: 36.0,
+            margin: 4.0,
+            color: Paint::Solid(Color::rgb(230, 40, 40)),
+            text_color: Paint::Solid(Color::WHITE),
+            outline: None,
+        }
+    }
+
+    /// A red dot without a count.
+    pub fn dot() -> Self {
+        Self {
+            font: None,
+            count: 1,
+            max: 99,
+            corner: Corner::TopRight,
+            size: 18.0,
+            margin: 8.0,
+            color: Paint::Solid(Color::rgb(230, 40, 40)),
+            text_color: Paint::Solid(Color::WHITE),
+            outline: None,
+        }
+    }
+
+    pub fn max(mut self, max: u32) -> Self {
+        self.max = max;
+        self
+    }
+
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn margin(mut self, margin: f32) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    pub fn color(mut self, paint: impl Into<Paint>) -> Self {
+        self.color = paint.into();
+        self
+    }
+
+    pub fn text_color(mut self, paint: impl Into<Paint>) -> Self {
+        self.text_color = paint.into();
+        self
+    }
+
+    /// Surround the badge with a `width`-pixel ring, usually in the background color.
+    pub fn outline(mut self, width: f32, paint: impl Into<Paint>) -> Self {
+        self.outline = Some((width, paint.into()));
+        self
+    }
+
+    /// The count as shown, capped at `max`.
+    fn count_text(&self) -> String {
+        if self.count > self.max {
+            format!("{}+", self.max)
+        } else {
+            self.count.to_string()
+        }
+    }
+}
+
+impl Canvas {
+    /// Draw a progress bar filling `rect`.
+    pub fn draw_progress_bar(&mut self, rect: Rect, bar: &ProgressBar) -> Result<(), RenderError> {
+        let value = clamp_unit(bar.value);
+        self.fill_rounded_rect(rect, bar.radius, bar.track.clone());
+
+        let filled = match bar.orientation {
+            Orientation::Horizontal => Rect::new(rect.x, rect.y, rect.width * value, rect.height),
+            Orientation::Vertical => {
+                let h = rect.height * value;
+                Rect::new(rect.x, rect.bottom() - h, rect.width, h)
+            }
+        };
+        if value > 0.0 {
+            self.push_clip_rounded_rect(rect, bar.radius);
+            self.fill_rect(filled, bar.fill.clone());
+            self.pop_clip();
+        }
+
+        match &bar.label {
+            Some(label) => label.draw_percent(self, rect, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Draw a ring or arc gauge centered in `rect`, sized to its shorter side.
+    pub fn draw_gauge(&mut self, rect: Rect, gauge: &Gauge) -> Result<(), RenderError> {
+        let value = clamp_unit(gauge.value);
+        let c = rect.center();
+        let r = (rect.width.min(rect.height) - gauge.thickness) / 2.0;
+        if r <= 0.0 {
+            return Ok(());
+        }
+
+        let sweep = gauge.end_angle - gauge.start_angle;
+        let full_ring = sweep.abs() >= TAU - 1e-3;
+        let arc = |to: f32| Path::new().arc(c.x, c.y, r, gauge.start_angle, to);
+        // A full ring is a closed circle, which has no ends to cap.
+        let ring = Path::new().circle(c, r);
+        let stroke = Stroke::new(gauge.thickness).cap(gauge.cap);
+
+        let track = if full_ring {
+            ring.clone()
+        } else {
+            arc(gauge.end_angle)
+        };
+        self.stroke_path(&track, &stroke, gauge.track.clone());
+        if value >= 1.0 && full_ring {
+            self.stroke_path(&ring, &stroke, gauge.fill.clone());
+        } else if value > 0.0 {
+            let path = arc(gauge.start_angle + sweep * value);
+            self.stroke_path(&path, &stroke, gauge.fill.clone());
+        }
+
+        match &gauge.label {
+            Some(label) => label.draw_percent(self, rect, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Draw a chart of `chart.values` filling `rect`.
+    pub fn draw_chart(&mut self, rect: Rect, chart: &Chart) -> Result<(), RenderError> {
+        let (lo, hi) = chart.scale();
+        let y_of = |v: f32, area: Rect| {
+            let t = ((v - lo) / (hi - lo)).clamp(0.0, 1.0);
+            area.bottom() - t * area.height
+        };
+
+        if chart.kind == ChartKind::Bars {
+            let n = chart.values.len() as f32;
+            let w = (rect.width - chart.bar_gap * (n - 1.0)) / n;
+            let base = y_of(0.0, rect);
+            for (i, &v) in chart.values.iter().enumerate() {
+                if !v.is_finite() || w <= 0.0 {
+                    continue;
+                }
+                let x = rect.x + i as f32 * (w + chart.bar_gap);
+                let y = y_of(v, rect);
+                let bar = Rect::from_ltrb(x, y.min(base), x + w, y.max(base));
+                self.fill_rect(bar, chart.color_for(v));
+            }
+        } else {
+            // Keep the stroke inside the rect.
+            let area = rect.inset(chart.line_width / 2.0);
+            let step = area.width / (chart.values.len().max(2) - 1) as f32;
+            let mut line = Path::new();
+            let mut fill = Path::new();
+            // x of the first and last point of the current run, and y of the last.
+            let mut run: Option<(f32, f32, f32)> = None;
+            // End a run, closing its area down to the bottom. A lone point has
+            // no segment to stroke, so it gets a flat one around it: half a
+            // step each side, or the full width when it is the only value.
+            let close = |line: Path, fill: Path, run: Option<(f32, f32, f32)>| {
+                let Some((x0, x1, y)) = run else {
+                    return (line, fill);
+                };
+                let (line, fill, x0, x1) = if x0 == x1 {
+                    let half = if chart.values.len() == 1 {
+                        area.width
+                    } else {
+                        step / 2.0
+                    };
+                    let (l, r) = ((x0 - half).max(area.x), (x0 + half).min(area.right()));
+                    let line = line.move_to(l, y).line_to(r, y);
+                    (line, fill.move_to(l, y).line_to(r, y), l, r)
+                } else {
+                    (line, fill, x0, x1)
+                };
+                let fill = fill
+                    .line_to(x1, area.bottom())
+                    .line_to(x0, area.bottom())
+                    .close();
+                (line, fill)
+            };
+            for (i, &v) in chart.values.iter().enumerate() {
+                if !v.is_finite() {
+                    (line, fill) = close(line, fill, run.take());
+                    continue;
+                }
+                let (x, y) = (area.x + i as f32 * step, y_of(v, area));
+                match run {
+                    Some((x0, _, _)) => {
+                        line = line.line_to(x, y);
+                        fill = fill.line_to(x, y);
+                        run = Some((x0, x, y));
+                    }
+                    None => {
+                        line = line.move_to(x, y);
+                        fill = fill.move_to(x, y);
+                        run = Some((x, x, y));
+                    }
+                }
+            }
+            let (line, fill) = close(line, fill, run);
+
+            let stroke = Stroke::new(chart.line_width)
+                .cap(LineCap::Round)
+                .join(LineJoin::Round);
+            // Each color band gets its own clip, so the colors meet edge to edge.
+            let mut bands = vec![(f32::NEG_INFINITY, chart.color.clone())];
+            bands.extend(chart.thresholds.iter().map(|&(t, c)| (t, Paint::Solid(c))));
+            for (i, (from, paint)) in bands.iter().enumerate() {
+                let top = bands.get(i + 1).map_or(rect.y, |(to, _)| y_of(*to, area));
+                let bottom = if from.is_finite() {
+                    y_of(*from, area)
+                } else {
+                    rect.bottom()
+                };
+                if bottom <= top {
+                    continue;
+                }
+                self.push_clip_rect(Rect::from_ltrb(rect.x, top, rect.right(), bottom));
+                if chart.kind == ChartKind::Area {
+                    let area_paint = match (&chart.area, paint) {
+                        (Some(p), _) if i == 0 => p.clone(),
+                        (_, Paint::Solid(c)) => Paint::Solid(c.with_alpha(c.a / 3)),
+                        (_, p) => p.clone(),
+                    };
+                    self.fill_path(&fill, FillRule::NonZero, area_paint);
+                }
+                self.stroke_path(&line, &stroke, paint.clone());
+                self.pop_clip();
+            }
+        }
+
+        let Some(label) = &chart.label else {
+            return Ok(());
+        };
+        let last = chart.values.iter().rev().find(|v| v.is_finite());
+        let value = last.map_or_else(|| "–".to_string(), |v| format_value(*v));
+        label.draw(self, rect, value, "", (HAlign::Right, VAlign::Top))
+    }
+
+    /// Draw a badge in `badge.corner` of `rect` — usually the whole canvas.
+    pub fn draw_badge(&mut self, rect: Rect, badge: &Badge) -> Result<(), RenderError> {
+        if badge.count == 0 {
+            return Ok(());
+        }
+        let h = badge.size;
+        let text = badge.font.as_ref().map(|_| badge.count_text());
+
+        // Pad the text by half the height, up to a pill 1.75× as wide as tall;
+        // past that the text shrinks instead.
+        let (mut text_size, mut w) = (h * 0.6, h);
+        if let (Some(font), Some(text)) = (&badge.font, &text) {
+            let (pad, max_w) = (h * 0.5, h * 1.75);
+            let text_w = measure_line(font, text_size, text);
+            if text_w > max_w - pad {
+                text_size *= (max_w - pad) / text_w;
+            }
+            w = (text_w.min(max_w - pad) + pad).max(h);
+        }
+
+        let inset = badge.margin + badge.outline.as_ref().map_or(0.0, |(ow, _)| *ow);
+        let x = match badge.corner {
+            Corner::TopLeft | Corner::BottomLeft => rect.x + inset,
+            Corner::TopRight | Corner::BottomRight => rect.right() - inset - w,
+        };
+        let y = match badge.corner {
+            Corner::TopLeft | Corner::TopRight => rect.y + inset,
+            Corner::BottomLeft | Corner::BottomRight => rect.bottom() - inset - h,
+        };
+        let pill = Rect::new(x, y, w, h);
+
+        if let Some((ow, paint)) = &badge.outline {
+            self.fill_rounded_rect(pill.inset(-ow), h / 2.0 + ow, paint.clone());
+        }
+        self.fill_rounded_rect(pill, h / 2.0, badge.color.clone());
+
+        let (Some(font), Some(text)) = (&badge.font, text) else {
+            return Ok(());
+        };
+        // Center the digits' ink rather than the font's full line box.
+        let baseline = pill.center().y + digit_height(font, text_size) / 2.0;
+        let line = TextLine {
+            width_px: measure_line(font, text_size, &text),
+            text,
+        };
+        let opts = TextOptions::new(font.clone(), text_size)
+            .color(badge.text_color.clone())
+            .v_align(VAlign::Baseline(baseline));
+        self.draw_text_in(pill, &[line], &opts).map(|_| ())
+    }
+}
+
+/// Height of the digit `0` above the baseline at `size` pixels.
+fn digit_height(font: &FontHandle, size: f32) -> f32 {
+    let glyph = font.arc().glyph_id('0').with_scale(size);
+    match font.arc().outline_glyph(glyph) {
+        Some(outline) => -outline.px_bounds().min.y,
+        None => font.arc().as_scaled(size).ascent() * 0.7,
+    }
+}
+
+/// Format a reading compactly: whole numbers without decimals, small values
+/// with one.
+fn format_value(v: f32) -> String {
+    if v.fract().abs() < 0.05 || v.abs() >= 100.0 {
+        // Small negatives round to zero; don't print them as `-0`.
+        let v = if v.round() == 0.0 { 0.0 } else { v };
+        format!("{v:.0}")
+    } else {
+        format!("{v:.1}")
+    }
+}
+
+/// `v` clamped to `[0, 1]`, with NaN as zero.
+fn clamp_unit(v: f32) -> f32 {
+    if v.is_nan() { 0.0 } else { v.clamp(0.0, 1.0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bar_fills_proportionally() {
+        let mut c = Canvas::new(100, 10);
+        let bar = ProgressBar::new(0.25)
+            .track(Color::TRANSPARENT)
+            .fill(Color::WHITE);
+        c.draw_progress_bar(Rect::new(0.0, 0.0, 100.0, 10.0), &bar)
+            .unwrap();
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(20, 5)[3], 255);
+        assert_eq!(img.get_pixel(30, 5)[3], 0);
+    }
+
+    #[test]
+    fn vertical_bar_fills_from_bottom() {
+        let mut c = Canvas::new(10, 100);
+        let bar = ProgressBar::new(0.5)
+            .vertical()
+            .track(Color::TRANSPARENT)
+            .fill(Color::WHITE);
+        c.draw_progress_bar(Rect::new(0.0, 0.0, 10.0, 100.0), &bar)
+            .unwrap();
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(5, 90)[3], 255);
+        assert_eq!(img.get_pixel(5, 10)[3], 0);
+    }
+
+    #[test]
+    fn chart_scales_to_data() {
+        let chart = Chart::line(&[3.0, 5.0, f32::NAN, 4.0]);
+        assert_eq!(chart.scale(), (3.0, 5.0));
+        assert_eq!(Chart::bars(&[3.0, 5.0]).scale(), (0.0, 5.0));
+        assert_eq!(Chart::line(&[2.0, 2.0]).scale(), (1.0, 3.0));
+        assert_eq!(Chart::line(&[]).range(0.0, 10.0).scale(), (0.0, 10.0));
+    }
+
+    #[test]
+    fn lone_points_draw_flat_segments() {
+        let draw = |chart: Chart| {
+            let mut c = Canvas::new(20, 10);
+            c.draw_chart(Rect::new(0.0, 0.0, 20.0, 10.0), &chart)
+                .unwrap();
+            c.finish().buf
+        };
+        // A single value spans the whole width.
+        let img = draw(Chart::line(&[5.0]));
+        assert_eq!(img.get_pixel(2, 5)[3], 255);
+        assert_eq!(img.get_pixel(17, 5)[3], 255);
+        assert_eq!(img.get_pixel(10, 1)[3], 0);
+        // A point between gaps gets a short segment, with area under it.
+        let img = draw(Chart::area(&[5.0, f32::NAN, 5.0, f32::NAN, 5.0]));
+        assert_eq!(img.get_pixel(10, 5)[3], 255);
+        assert!(img.get_pixel(10, 8)[3] > 0);
+        assert_eq!(img.get_pixel(6, 8)[3], 0);
+    }
+
+    #[test]
+    fn bars_take_threshold_colors() {
+        let red = Color::rgb(255, 0, 0);
+        let chart = Chart::bars(&[1.0, 9.0])
+            .range(0.0, 10.0)
+            .bar_gap(0.0)
+            .color(Color::WHITE)
+            .threshold(5.0, red);
+        let mut c = Canvas::new(20, 10);
+        c.draw_chart(Rect::new(0.0, 0.0, 20.0, 10.0), &chart)
+            .unwrap();
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(5, 9).0, [255, 255, 255, 255]);
+        assert_eq!(img.get_pixel(5, 5)[3], 0);
+        assert_eq!(img.get_pixel(15, 5).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn badge_count_overflows() {
+        let badge = Badge::dot();
+        assert_eq!(badge.clone().count_text(), "1");
+        assert_eq!(
+            Badge {
+                count: 120,
+                ..badge
+            }
+            .count_text(),
+            "99+"
+        );
+    }
+
+    #[test]
+    fn dot_sits_in_corner() {
+        let mut c = Canvas::new(40, 40);
+        let badge = Badge::dot().corner(Corner::BottomLeft);
+        c.draw_badge(Rect::new(0.0, 0.0, 40.0, 40.0), &badge)
+            .unwrap();
+        let img = c.finish().buf;
+        assert_eq!(img.get_pixel(17, 23)[3], 255);
+        assert_eq!(img.get_pixel(35, 5)[3], 0);
+    }
+
+    #[test]
+    fn ring_fills_clockwise_from_top() {
+        let mut c = Canvas::new(100, 100);
+        let gauge = Gauge::ring(0.25)
+            .thickness(10.0)
+            .cap(LineCap::Butt)
+            .track(Color::TRANSPARENT)
+            .fill(Color::WHITE);
+        c.draw_gauge(Rect::new(0.0, 0.0, 100.0, 100.0), &gauge)
+            .unwrap();
+        let img = c.finish().buf;
+        // First quarter: top to 3 o'clock.
+        assert_eq!(img.get_pixel(80, 20)[3], 255);
+        assert_eq!(img.get_pixel(20, 80)[3], 0);
+        assert_eq!(img.get_pixel(20, 20)[3], 0);
+    }
+
+    #[test]
+    fn small_negative_values_format_without_sign() {
+        assert_eq!(format_value(-0.04), "0");
+        assert_eq!(format_value(-0.0), "0");
+        assert_eq!(format_value(-0.4), "-0.4");
+        assert_eq!(format_value(-3.0), "-3");
+    }
+
+    #[test]
+    fn inverted_range_is_swapped() {
+        let chart = Chart::line(&[5.0]).range(10.0, 0.0);
+        assert_eq!(chart.scale(), (0.0, 10.0));
+        assert_eq!(Chart::line(&[5.0]).range(3.0, 3.0).scale(), (2.0, 4.0));
+    }
+
+    #[test]
+    fn badge_text_stays_inside_the_pill() {
+        let font = crate::test_fonts::dejavu();
+        // Bounds of the pill (any alpha) and of the white text ink.
+        let draw = |badge: &Badge| {
+            let mut c = Canvas::new(120, 50);
+            c.draw_badge(Rect::new(0.0, 0.0, 120.0, 50.0), badge)
+                .unwrap();
+            let img = c.finish().buf;
+            let bounds = |hit: &dyn Fn(&image::Rgba<u8>) -> bool| {
+                let (mut x0, mut x1, mut y0, mut y1) = (u32::MAX, 0, u32::MAX, 0);
+                for (x, y, p) in img.enumerate_pixels() {
+                    if hit(p) {
+                        (x0, x1, y0, y1) = (x0.min(x), x1.max(x), y0.min(y), y1.max(y));
+                    }
+                }
+                (x0, x1, y0, y1)
+            };
+            (bounds(&|p| p[3] > 0), bounds(&|p| p[1] > 128))
+        };
+        let inside = |(px0, px1, py0, py1): (u32, u32, u32, u32), (tx0, tx1, ty0, ty1)| {
+            tx0 > px0 + 4 && tx1 + 4 < px1 && ty0 > py0 + 2 && ty1 + 2 < py1
+        };
+
+        // "99+" fits at the normal size: the pill widens around it.
+        let badge = Badge::count(font.clone(), 120);
+        let (pill, ink) = draw(&badge);
+        assert!(inside(pill, ink), "{pill:?} {ink:?}");
+        assert!(pill.1 - pill.0 > 40, "{pill:?}");
+
+        // Too long for a 1.75× pill: the pill stops growing and the text shrinks.
+        let badge = Badge::count(font.clone(), 123_456).max(9999);
+        assert!(measure_line(&font, 36.0 * 0.6, "9999+") > 36.0 * 1.25);
+        let (pill, ink) = draw(&badge);
+        assert!(inside(pill, ink), "{pill:?} {ink:?}");
+        assert!(pill.1 - pill.0 <= 64, "{pill:?}");
+    }
+}